@@ -1,16 +1,17 @@
 use ff4::character;
 use ff4::rom;
+use ff4::Error;
 
 pub struct Character {
-    pub class: u8,
+    pub class: character::Class,
 }
 
 impl Character {
-    pub fn new(rom: &rom::Rom, index: usize) -> Self {
-        let initial_stats = character::CharacterInitial::new(rom, index);
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let initial_stats = character::CharacterInitial::new(rom, index)?;
 
-        Character {
+        Ok(Character {
             class: initial_stats.class,
-        }
+        })
     }
 }
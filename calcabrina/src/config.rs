@@ -1,6 +1,11 @@
+use std::fs;
+use std::io;
+
 use clap::Clap;
 use ggez_goodies::{Point2, Vector2};
 
+use ff4::rom;
+
 #[derive(Clap)]
 #[clap(version = "0.1.0", author = "Jason Lynch <jason@calindora.com>")]
 pub struct Config {
@@ -18,9 +23,30 @@ pub struct Config {
     /// Experimental support for 16:9 output. (Or approximately 3:2 if using the incorrect aspect ratio.)
     #[clap(short, long)]
     pub widescreen: bool,
+
+    /// Accept modified ROM images (such as romhacks) whose hashes are not recognized.
+    #[clap(short, long)]
+    pub lenient: bool,
+
+    /// IPS or BPS patch to apply to the ROM image before loading. May be given more than once.
+    #[clap(short, long, number_of_values = 1)]
+    pub patch: Vec<String>,
 }
 
 impl Config {
+    pub fn get_rom_options(&self) -> io::Result<rom::Options> {
+        let patches = self
+            .patch
+            .iter()
+            .map(fs::read)
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(rom::Options {
+            lenient: self.lenient,
+            patches,
+        })
+    }
+
     pub fn get_base_window_size(&self) -> (f32, f32) {
         if self.widescreen {
             (256.0 * 15.0 * 4.0 / 14.0 / 3.0, 224.0 * 15.0 / 14.0)
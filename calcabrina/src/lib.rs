@@ -45,7 +45,12 @@ struct MainState {
 
 impl MainState {
     fn new(ctx: &mut Context, config: config::Config) -> Self {
-        let rom = rom::Rom::new(&config.filename).unwrap_or_else(|err| {
+        let options = config.get_rom_options().unwrap_or_else(|err| {
+            error!("Error loading patch file: {}", err);
+            process::exit(1);
+        });
+
+        let rom = rom::Rom::new(&config.filename, &options).unwrap_or_else(|err| {
             error!("Error loading ROM file: {}", err);
             process::exit(1);
         });
@@ -53,12 +58,22 @@ impl MainState {
         info!("ROM title: {}", rom.title());
         info!("ROM description: {}", rom.description());
 
-        let world = world::World::new(config, rom);
+        if rom.is_modified() {
+            info!("ROM image has been modified from the original release");
+        }
+
+        let world = world::World::new(config, rom).unwrap_or_else(|err| {
+            error!("Error reading ROM data: {}", err);
+            process::exit(1);
+        });
 
         let mut scenes = scenes::Stack::new(ctx, world);
 
-        let scene = Box::new(scenes::title::TitleScene::new(ctx, &mut scenes.world));
-        scenes.push(scene);
+        let scene = scenes::title::TitleScene::new(ctx, &mut scenes.world).unwrap_or_else(|err| {
+            error!("Error reading ROM data: {}", err);
+            process::exit(1);
+        });
+        scenes.push(Box::new(scene));
 
         Self {
             input_binding: input::create_input_binding(),
@@ -8,6 +8,7 @@ use image::RgbaImage;
 use ff4::map;
 use ff4::misc;
 use ff4::rom;
+use ff4::Error;
 
 use crate::config;
 use crate::util;
@@ -33,12 +34,16 @@ pub struct Map {
 }
 
 impl Map {
-    pub fn new_outdoor(config: &config::Config, rom: &rom::Rom, index: OutdoorMap) -> Self {
+    pub fn new_outdoor(
+        config: &config::Config,
+        rom: &rom::Rom,
+        index: OutdoorMap,
+    ) -> Result<Self, Error> {
         let (window_width, window_height) = config.get_window_size();
-        let map = map::Map::new_outdoor(rom, index);
-        let tileset = map::OutdoorTileset::new(rom, index);
+        let map = map::Map::new_outdoor(rom, index)?;
+        let tileset = map::OutdoorTileset::new(rom, index)?;
 
-        Self {
+        Ok(Self {
             frame_counter: 0,
             map,
             index,
@@ -47,12 +52,12 @@ impl Map {
             tile_cache: TileCache::new_outdoor(&tileset),
             tileset,
             transform: vec![None; window_width * window_height],
-        }
+        })
     }
 
-    pub fn update(&mut self, world: &mut World) {
+    pub fn update(&mut self, world: &mut World) -> Result<(), Error> {
         self.frame_counter += 1;
-        self.animate_water_tiles(world);
+        self.animate_water_tiles(world)
     }
 
     pub fn height(&self) -> usize {
@@ -234,24 +239,28 @@ impl Map {
         Ok(img)
     }
 
-    fn animate_water_tiles(&mut self, world: &mut World) {
+    fn animate_water_tiles(&mut self, world: &mut World) -> Result<(), Error> {
         if self.frame_counter % 2 == 0 {
             match self.index {
-                OutdoorMap::Overworld | OutdoorMap::Underworld => self.animate_ocean_tiles(world),
+                OutdoorMap::Overworld | OutdoorMap::Underworld => {
+                    self.animate_ocean_tiles(world)?
+                }
                 _ => {}
             }
         }
 
         if let OutdoorMap::Overworld = self.index {
-            self.animate_waterfall_tiles(world);
+            self.animate_waterfall_tiles(world)?;
         }
+
+        Ok(())
     }
 
-    fn animate_ocean_tiles(&mut self, world: &mut World) {
+    fn animate_ocean_tiles(&mut self, world: &mut World) -> Result<(), Error> {
         let (tile_offset, line) = misc::get_ocean_animation_line(
             &world.rom,
             usize::try_from(self.frame_counter >> 1).unwrap(),
-        );
+        )?;
 
         let tile_index = match self.index {
             OutdoorMap::Overworld => TILE_INDEX_OCEAN_OVERWORLD + tile_offset,
@@ -274,13 +283,15 @@ impl Map {
         }
 
         self.tile_cache.tiles[tile_index][line * 8] = last_value;
+
+        Ok(())
     }
 
-    fn animate_waterfall_tiles(&mut self, world: &mut World) {
+    fn animate_waterfall_tiles(&mut self, world: &mut World) -> Result<(), Error> {
         let (tile_offset, column) = misc::get_waterfall_animation_column(
             &world.rom,
             usize::try_from(self.frame_counter).unwrap(),
-        );
+        )?;
 
         let tile_index = TILE_INDEX_WATERFALL + tile_offset;
 
@@ -302,6 +313,8 @@ impl Map {
 
             self.tile_cache.tiles[tile_index][column] = last_value;
         }
+
+        Ok(())
     }
 }
 
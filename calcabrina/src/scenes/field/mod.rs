@@ -4,7 +4,9 @@ use ggez::graphics;
 use ggez::{Context, GameResult};
 use ggez_goodies::scene;
 use ggez_goodies::Point2;
-use log::debug;
+use log::{debug, error};
+
+use ff4::Error;
 
 use crate::input;
 use crate::scenes;
@@ -22,14 +24,14 @@ pub struct FieldScene {
 }
 
 impl FieldScene {
-    pub fn new(_ctx: &mut Context, world: &mut World) -> Self {
-        let map = map::Map::new_outdoor(&world.config, &world.rom, world.map_index);
+    pub fn new(_ctx: &mut Context, world: &mut World) -> Result<Self, Error> {
+        let map = map::Map::new_outdoor(&world.config, &world.rom, world.map_index)?;
 
-        FieldScene {
+        Ok(FieldScene {
             done: false,
             map,
-            player_sprite: sprite::FieldSprite::new_player(&world.rom, 0),
-        }
+            player_sprite: sprite::FieldSprite::new_player(&world.rom, 0)?,
+        })
     }
 
     pub fn do_player_movement(&mut self, world: &mut World) {
@@ -107,7 +109,14 @@ impl scene::Scene<World, input::Event> for FieldScene {
         }
 
         if self.map.index() != world.map_index {
-            self.map = map::Map::new_outdoor(&world.config, &world.rom, world.map_index);
+            match map::Map::new_outdoor(&world.config, &world.rom, world.map_index) {
+                Ok(map) => self.map = map,
+                Err(err) => {
+                    error!("Error reading ROM data: {}", err);
+                    ggez::event::quit(ctx);
+                    return scene::SceneSwitch::None;
+                }
+            }
         }
 
         while world.party[world.player_sprite_index].is_none() {
@@ -115,14 +124,26 @@ impl scene::Scene<World, input::Event> for FieldScene {
         }
 
         if let Some(character) = &world.party[world.player_sprite_index] {
-            if self.player_sprite.class != usize::from(character.class) {
-                self.player_sprite =
-                    sprite::FieldSprite::new_player(&world.rom, usize::from(character.class));
+            let class = usize::from(character.class.id());
+
+            if self.player_sprite.class != class {
+                match sprite::FieldSprite::new_player(&world.rom, class) {
+                    Ok(sprite) => self.player_sprite = sprite,
+                    Err(err) => {
+                        error!("Error reading ROM data: {}", err);
+                        ggez::event::quit(ctx);
+                        return scene::SceneSwitch::None;
+                    }
+                }
             }
         }
 
         self.do_player_movement(world);
-        self.map.update(world);
+
+        if let Err(err) = self.map.update(world) {
+            error!("Error reading ROM data: {}", err);
+            ggez::event::quit(ctx);
+        }
 
         scene::SceneSwitch::None
     }
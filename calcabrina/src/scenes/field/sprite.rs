@@ -5,6 +5,7 @@ use ggez::{Context, GameResult};
 use ggez_goodies::Point2;
 
 use ff4::rom;
+use ff4::Error;
 
 use crate::util;
 use crate::util::Direction;
@@ -26,8 +27,8 @@ pub struct FieldSprite {
 }
 
 impl FieldSprite {
-    pub fn new_player(rom: &rom::Rom, index: usize) -> Self {
-        let sheet = ff4::graphics::FieldSpriteSheet::new_player(rom, index);
+    pub fn new_player(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let sheet = ff4::graphics::FieldSpriteSheet::new_player(rom, index)?;
 
         let frames = (0..ff4::graphics::FIELD_SPRITE_PLAYER_FRAME_COUNT)
             .map(|frame| {
@@ -70,14 +71,14 @@ impl FieldSprite {
             })
             .collect();
 
-        let palette = ff4::graphics::get_field_sprite_palette_player(rom, sheet.palette_index);
+        let palette = ff4::graphics::get_field_sprite_palette_player(rom, sheet.palette_index)?;
 
-        Self {
+        Ok(Self {
             class: index,
             frames,
             palette,
             sheet,
-        }
+        })
     }
 
     pub fn get_draw_coordinates(&self, world: &World) -> Point2 {
@@ -1,7 +1,9 @@
 use ggez::graphics;
 use ggez_goodies::scene;
+use log::error;
 
 use ff4::title;
+use ff4::Error;
 
 use crate::input;
 use crate::scenes;
@@ -17,13 +19,13 @@ pub struct TitleScene {
 }
 
 impl TitleScene {
-    pub fn new(_ctx: &mut ggez::Context, world: &mut World) -> Self {
-        TitleScene {
+    pub fn new(_ctx: &mut ggez::Context, world: &mut World) -> Result<Self, Error> {
+        Ok(TitleScene {
             brightness: 0,
             delay: 30,
             done: false,
-            title: title::Title::new(&world.rom),
-        }
+            title: title::Title::new(&world.rom)?,
+        })
     }
 }
 
@@ -38,7 +40,14 @@ impl scene::Scene<World, input::Event> for TitleScene {
         }
 
         if self.done && self.brightness == 0 && self.delay == 0 {
-            scene::SceneSwitch::Replace(Box::new(scenes::field::FieldScene::new(ctx, world)))
+            match scenes::field::FieldScene::new(ctx, world) {
+                Ok(scene) => scene::SceneSwitch::Replace(Box::new(scene)),
+                Err(err) => {
+                    error!("Error reading ROM data: {}", err);
+                    ggez::event::quit(ctx);
+                    scene::SceneSwitch::None
+                }
+            }
         } else {
             scene::SceneSwitch::None
         }
@@ -1,4 +1,5 @@
 use ff4::rom;
+use ff4::Error;
 
 use crate::character;
 use crate::config;
@@ -23,16 +24,16 @@ pub struct World {
 }
 
 impl World {
-    pub fn new(config: config::Config, rom: rom::Rom) -> Self {
+    pub fn new(config: config::Config, rom: rom::Rom) -> Result<Self, Error> {
         let party = [
-            Some(character::Character::new(&rom, 0)),
+            Some(character::Character::new(&rom, 0)?),
             None,
             None,
             None,
             None,
         ];
 
-        Self {
+        Ok(Self {
             config,
             input: input::State::new(),
             rom,
@@ -42,6 +43,6 @@ impl World {
             player_movement: util::Movement::None,
             player_sprite_index: 0,
             party,
-        }
+        })
     }
 }
@@ -1,13 +1,31 @@
-use std::process;
-
-use log::error;
+use bitflags::bitflags;
 use nom::{
+    error::ErrorKind,
+    multi::count,
     number::complete::{le_u16, le_u24, le_u32, le_u8},
     IResult,
 };
 
 use crate::rom;
-use crate::rom_map;
+use crate::rom_map::record::RecordId;
+use crate::text;
+use crate::Error;
+
+pub const MAXIMUM_LEVEL: u8 = 99;
+pub const RANDOM_PATTERN_LEVEL: u8 = 70;
+pub const RANDOM_PATTERN_COUNT: usize = 8;
+
+const MAXIMUM_STAT: u8 = 99;
+const MAXIMUM_HP: u16 = 9999;
+const MAXIMUM_MP: u16 = 999;
+
+const LEVEL_UP_STRENGTH: u8 = 0x80;
+const LEVEL_UP_AGILITY: u8 = 0x40;
+const LEVEL_UP_VITALITY: u8 = 0x20;
+const LEVEL_UP_WISDOM: u8 = 0x10;
+const LEVEL_UP_WILL: u8 = 0x08;
+const LEVEL_UP_AMOUNT: u8 = 0x07;
+const LEVEL_UP_DECREASE: u8 = 0x07;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Handedness {
@@ -16,14 +34,125 @@ pub enum Handedness {
     Both,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Class {
+    DarkKnight,
+    Dragoon,
+    YoungCaller,
+    Sage,
+    Bard,
+    WhiteWizard,
+    Karate,
+    BlackWizard,
+    YoungWhiteWizard,
+    Paladin,
+    Chief,
+    Caller,
+    Ninja,
+    Lunarian,
+}
+
+/// Identifies a character independently of their class, so Cecil keeps the same id as a Dark
+/// Knight and as a Paladin.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CharacterId(pub u8);
+
+bitflags! {
+    pub struct Status: u32 {
+        const POISON = 0x0000_0001;
+        const DARKNESS = 0x0000_0002;
+        const SILENCE = 0x0000_0004;
+        const PIG = 0x0000_0008;
+        const MINI = 0x0000_0010;
+        const TOAD = 0x0000_0020;
+        const STONE = 0x0000_0040;
+        const KO = 0x0000_0080;
+        const CALCIFYING_1 = 0x0000_0100;
+        const CALCIFYING_2 = 0x0000_0200;
+        const BERSERK = 0x0000_0400;
+        const CHARM = 0x0000_0800;
+        const SLEEP = 0x0000_1000;
+        const PARALYSIS = 0x0000_2000;
+        const FLOAT = 0x0000_4000;
+        const CURSE = 0x0000_8000;
+        const COUNTDOWN = 0x0001_0000;
+        const WALL = 0x0002_0000;
+        const FOCUS = 0x0004_0000;
+        const DEFEND = 0x0008_0000;
+        const HIDE = 0x0010_0000;
+        const JUMP = 0x0020_0000;
+        const COVER = 0x0040_0000;
+        const STOP = 0x0080_0000;
+        const MAGNETIZED = 0x0100_0000;
+        const TWIN = 0x0200_0000;
+        const INVISIBLE = 0x0400_0000;
+        const SING = 0x0800_0000;
+
+        /// Ailments that last beyond the end of a battle.
+        const PERMANENT = 0x0000_00FF;
+        /// Ailments and states that are cleared when a battle ends.
+        const TEMPORARY = 0x0FFF_FF00;
+    }
+}
+
+impl Class {
+    pub fn from_id(id: u8) -> Option<Class> {
+        match id {
+            0x00 => Some(Class::DarkKnight),
+            0x01 => Some(Class::Dragoon),
+            0x02 => Some(Class::YoungCaller),
+            0x03 => Some(Class::Sage),
+            0x04 => Some(Class::Bard),
+            0x05 => Some(Class::WhiteWizard),
+            0x06 => Some(Class::Karate),
+            0x07 => Some(Class::BlackWizard),
+            0x08 => Some(Class::YoungWhiteWizard),
+            0x09 => Some(Class::Paladin),
+            0x0A => Some(Class::Chief),
+            0x0B => Some(Class::Caller),
+            0x0C => Some(Class::Ninja),
+            0x0D => Some(Class::Lunarian),
+            _ => None,
+        }
+    }
+
+    /// The class's index in the ROM's per-class tables, such as the field sprite sheets.
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Class::DarkKnight => "Dark Knight",
+            Class::Dragoon => "Dragoon",
+            Class::YoungCaller | Class::Caller => "Caller",
+            Class::Sage => "Sage",
+            Class::Bard => "Bard",
+            Class::WhiteWizard | Class::YoungWhiteWizard => "White Wizard",
+            Class::Karate => "Karate",
+            Class::BlackWizard => "Black Wizard",
+            Class::Paladin => "Paladin",
+            Class::Chief => "Chief",
+            Class::Ninja => "Ninja",
+            Class::Lunarian => "Lunarian",
+        }
+    }
+}
+
+impl CharacterId {
+    pub fn name(self, rom: &rom::Rom) -> Result<String, Error> {
+        text::read_character_name(rom, self.0.into())
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct CharacterInitial {
     pub handedness: Handedness,
-    pub id: u8,
+    pub id: CharacterId,
     pub long_range: bool,
-    pub class: u8,
+    pub class: Class,
     pub level: u8,
-    pub status: u32,
+    pub status: Status,
     pub hp_current: u16,
     pub hp_maximum: u16,
     pub mp_current: u16,
@@ -42,19 +171,120 @@ pub struct CharacterInitial {
 }
 
 impl CharacterInitial {
-    pub fn new(rom: &rom::Rom, index: usize) -> Self {
-        let record = rom_map::record::CHARACTER_STATS_INITIAL;
-        let bytes = rom.read_bytes(record, index);
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        rom.parse_record(RecordId::CharacterStatsInitial, index, parse_initial_stats)
+    }
+}
 
-        let (_, result) = parse_initial_stats(bytes).unwrap_or_else(|err| {
-            error!("Parsing Error: {}", err);
-            process::exit(1);
-        });
+/// The stat changes for a single level. HP and MP grow by a random amount between the base value
+/// and an eighth more.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct LevelUp {
+    pub stats: u8,
+    pub stat_change: i8,
+    pub hp: u16,
+    pub mp: u8,
+}
 
-        result
+/// The level-ups from level 2 to 70, followed by the patterns chosen from at random for every
+/// level after that.
+#[derive(Debug, PartialEq)]
+pub struct LevelUpTable {
+    pub levels: Vec<LevelUp>,
+    pub random_patterns: Vec<LevelUp>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub level: u8,
+    pub hp_maximum: u16,
+    pub mp_maximum: u16,
+    pub strength: u8,
+    pub agility: u8,
+    pub vitality: u8,
+    pub wisdom: u8,
+    pub will: u8,
+}
+
+impl LevelUp {
+    fn apply(&self, stats: &mut Stats, roll: &mut dyn FnMut(usize) -> usize) {
+        let change = |stat: &mut u8, flag: u8| {
+            if self.stats & flag > 0 {
+                *stat = (i16::from(*stat) + i16::from(self.stat_change))
+                    .max(1)
+                    .min(i16::from(MAXIMUM_STAT)) as u8;
+            }
+        };
+
+        change(&mut stats.strength, LEVEL_UP_STRENGTH);
+        change(&mut stats.agility, LEVEL_UP_AGILITY);
+        change(&mut stats.vitality, LEVEL_UP_VITALITY);
+        change(&mut stats.wisdom, LEVEL_UP_WISDOM);
+        change(&mut stats.will, LEVEL_UP_WILL);
+
+        let hp = self.hp + roll(usize::from(self.hp / 8) + 1) as u16;
+        let mp = u16::from(self.mp) + roll(usize::from(self.mp / 8) + 1) as u16;
+
+        stats.hp_maximum = stats.hp_maximum.saturating_add(hp).min(MAXIMUM_HP);
+        stats.mp_maximum = stats.mp_maximum.saturating_add(mp).min(MAXIMUM_MP);
+        stats.level += 1;
+    }
+}
+
+impl LevelUpTable {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        rom.parse_record(RecordId::LevelUpTable, index, parse_level_up_table)
+    }
+
+    /// Computes a character's stats at a level by applying each level-up in turn. `roll(n)` must
+    /// return a value below `n`; it chooses HP and MP growth and the pattern used past level 70,
+    /// so `|_| 0` gives the minimum growth.
+    pub fn stats_at_level(
+        &self,
+        initial: &CharacterInitial,
+        level: u8,
+        roll: &mut dyn FnMut(usize) -> usize,
+    ) -> Stats {
+        let mut stats = Stats::from(initial);
+
+        while stats.level < level.min(MAXIMUM_LEVEL) {
+            let level_up = if stats.level < RANDOM_PATTERN_LEVEL {
+                self.levels[usize::from(stats.level - 1)]
+            } else {
+                self.random_patterns[roll(RANDOM_PATTERN_COUNT)]
+            };
+
+            level_up.apply(&mut stats, roll);
+        }
+
+        stats
     }
 }
 
+impl From<&CharacterInitial> for Stats {
+    fn from(initial: &CharacterInitial) -> Self {
+        Stats {
+            level: initial.level,
+            hp_maximum: initial.hp_maximum,
+            mp_maximum: initial.mp_maximum,
+            strength: initial.strength,
+            agility: initial.agility,
+            vitality: initial.vitality,
+            wisdom: initial.wisdom,
+            will: initial.will,
+        }
+    }
+}
+
+/// Returns the experience needed to advance from a level to the next.
+pub fn get_experience_to_next_level(rom: &rom::Rom, level: u8) -> Result<u32, Error> {
+    rom.parse_record(
+        RecordId::ExperienceTable,
+        usize::from(level.max(1) - 1),
+        parse_experience,
+    )
+}
+
 pub fn parse_initial_stats(input: &[u8]) -> IResult<&[u8], CharacterInitial> {
     let (input, handedness_id) = le_u8(input)?;
     let handedness = match handedness_id & 0xC0 {
@@ -62,14 +292,20 @@ pub fn parse_initial_stats(input: &[u8]) -> IResult<&[u8], CharacterInitial> {
         0x80 => Handedness::Right,
         _ => Handedness::Both,
     };
-    let id = handedness_id & 0x3F;
+    let id = CharacterId(handedness_id & 0x3F);
 
     let (input, long_range_class) = le_u8(input)?;
     let long_range = long_range_class & 0x40 > 0;
-    let class = long_range_class & 0x3F;
+    let class = Class::from_id(long_range_class & 0x3F)
+        .ok_or(nom::Err::Error((input, ErrorKind::MapOpt)))?;
 
     let (input, level) = le_u8(input)?;
+    if level == 0 {
+        return Err(nom::Err::Error((input, ErrorKind::Verify)));
+    }
+
     let (input, status) = le_u32(input)?;
+    let status = Status::from_bits_truncate(status);
     let (input, hp_current) = le_u16(input)?;
     let (input, hp_maximum) = le_u16(input)?;
     let (input, mp_current) = le_u16(input)?;
@@ -116,9 +352,83 @@ pub fn parse_initial_stats(input: &[u8]) -> IResult<&[u8], CharacterInitial> {
     ))
 }
 
+pub fn parse_level_up(input: &[u8]) -> IResult<&[u8], LevelUp> {
+    let (input, stats_amount) = le_u8(input)?;
+    let (input, hp) = le_u16(input)?;
+    let (input, mp) = le_u8(input)?;
+    let (input, _) = le_u8(input)?; // unused
+
+    let stat_change = match stats_amount & LEVEL_UP_AMOUNT {
+        LEVEL_UP_DECREASE => -1,
+        amount => amount as i8,
+    };
+
+    Ok((
+        input,
+        LevelUp {
+            stats: stats_amount & !LEVEL_UP_AMOUNT,
+            stat_change,
+            hp,
+            mp,
+        },
+    ))
+}
+
+pub fn parse_level_up_table(input: &[u8]) -> IResult<&[u8], LevelUpTable> {
+    let (input, levels) = count(parse_level_up, usize::from(RANDOM_PATTERN_LEVEL - 1))(input)?;
+    let (input, random_patterns) = count(parse_level_up, RANDOM_PATTERN_COUNT)(input)?;
+
+    Ok((
+        input,
+        LevelUpTable {
+            levels,
+            random_patterns,
+        },
+    ))
+}
+
+pub fn parse_experience(input: &[u8]) -> IResult<&[u8], u32> {
+    le_u24(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_character_initial_new() {
+        let mut bytes = [0; 0x20];
+        bytes[0] = 0x81;
+        bytes[1] = 0x05;
+        bytes[2] = 0x0A;
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::CharacterStatsInitial, 3, &bytes)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let character = CharacterInitial::new(&rom, 3).unwrap();
+
+        assert_eq!(character.handedness, Handedness::Right);
+        assert_eq!(character.id, CharacterId(1));
+        assert_eq!(character.class, Class::WhiteWizard);
+        assert_eq!(character.level, 10);
+
+        bytes[2] = 0x00;
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::CharacterStatsInitial, 3, &bytes)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            CharacterInitial::new(&rom, 3),
+            Err(Error::Parse { .. })
+        ));
+    }
 
     #[test]
     fn test_parse_initial_stats() {
@@ -132,11 +442,11 @@ mod tests {
                 &[][..],
                 CharacterInitial {
                     handedness: Handedness::Both,
-                    id: 15,
+                    id: CharacterId(15),
                     long_range: true,
-                    class: 3,
+                    class: Class::Sage,
                     level: 8,
-                    status: 0,
+                    status: Status::empty(),
                     hp_current: 1200,
                     hp_maximum: 1200,
                     mp_current: 999,
@@ -156,4 +466,94 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_class() {
+        assert_eq!(Class::from_id(0x09), Some(Class::Paladin));
+        assert_eq!(Class::from_id(0x0E), None);
+        assert_eq!(Class::Paladin.id(), 0x09);
+        assert_eq!(Class::YoungCaller.name(), Class::Caller.name());
+    }
+
+    #[test]
+    fn test_parse_initial_stats_status() {
+        let mut bytes = [0; 0x20];
+        bytes[1] = 0x0E;
+        bytes[2] = 0x01;
+        assert!(parse_initial_stats(&bytes).is_err());
+
+        bytes[1] = 0x0D;
+        bytes[3..7].copy_from_slice(&[0x81, 0x00, 0x02, 0x00]);
+        let (_, character) = parse_initial_stats(&bytes).unwrap();
+
+        assert_eq!(character.class, Class::Lunarian);
+        assert_eq!(character.status, Status::POISON | Status::KO | Status::WALL);
+        assert!(character.status.intersects(Status::PERMANENT));
+    }
+
+    #[test]
+    fn test_parse_level_up() {
+        assert_eq!(
+            parse_level_up(&[0xA3, 0x2C, 0x01, 0x10, 0x00]),
+            Ok((
+                &[][..],
+                LevelUp {
+                    stats: 0xA0,
+                    stat_change: 3,
+                    hp: 300,
+                    mp: 16,
+                }
+            ))
+        );
+        assert_eq!(
+            parse_level_up(&[0x47, 0, 0, 0, 0]).unwrap().1.stat_change,
+            -1
+        );
+    }
+
+    #[test]
+    fn test_stats_at_level() {
+        let mut table = vec![0; 0x181];
+        // Level 8 to 9: strength and will +2, 80 HP, 16 MP.
+        table[7 * 5..8 * 5].copy_from_slice(&[0x8A, 0x50, 0x00, 0x10, 0x00]);
+        // Level 9 to 10: agility -1.
+        table[8 * 5..9 * 5].copy_from_slice(&[0x47, 0x00, 0x00, 0x00, 0x00]);
+        // The second random pattern: every stat +1.
+        table[70 * 5..71 * 5].copy_from_slice(&[0xF9, 0x08, 0x00, 0x00, 0x00]);
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::LevelUpTable, 2, &table)
+            .write(RecordId::ExperienceTable, 9, &[0x10, 0x27, 0x00])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let table = LevelUpTable::new(&rom, 2).unwrap();
+        assert_eq!(table.levels.len(), 69);
+        assert_eq!(table.random_patterns.len(), RANDOM_PATTERN_COUNT);
+
+        let (_, initial) = parse_initial_stats(&[
+            0xCF, 0x43, 0x08, 0x00, 0x00, 0x00, 0x00, 0xB0, 0x04, 0xB0, 0x04, 0xE7, 0x03, 0xE7,
+            0x03, 0x10, 0x20, 0x30, 0x40, 0x50, 0x03, 0x28, 0x0A, 0xB8, 0x0B, 0x00, 0x00, 0x10,
+            0x00, 0xC4, 0x09, 0x00,
+        ])
+        .unwrap();
+
+        let stats = table.stats_at_level(&initial, 10, &mut |_| 0);
+        assert_eq!(stats.level, 10);
+        assert_eq!(stats.hp_maximum, 1280);
+        assert_eq!(stats.mp_maximum, MAXIMUM_MP);
+        assert_eq!(stats.strength, 18);
+        assert_eq!(stats.agility, 31);
+        assert_eq!(stats.will, 82);
+
+        let stats = table.stats_at_level(&initial, 10, &mut |n| n - 1);
+        assert_eq!(stats.hp_maximum, 1290);
+
+        let stats = table.stats_at_level(&initial, 72, &mut |n| n.min(2) - 1);
+        assert_eq!(stats.level, 72);
+        assert_eq!(stats.vitality, 50);
+
+        assert_eq!(get_experience_to_next_level(&rom, 10).unwrap(), 10000);
+    }
 }
@@ -0,0 +1,151 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+use crate::rom::format::Normalization;
+use crate::rom_map::{self, Version};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    UnknownHash(String),
+    BadSize(usize),
+    Parse {
+        record: &'static str,
+        address: usize,
+    },
+    UnmappedAddress(usize),
+    UnmappedOffset(usize),
+    UnmappedRecord {
+        record: &'static str,
+        version: Version,
+    },
+    IndexOutOfRange {
+        record: &'static str,
+        index: usize,
+    },
+    OutOfRange {
+        address: usize,
+        length: usize,
+    },
+    InvalidPatch(&'static str),
+    PatchChecksum {
+        kind: &'static str,
+        expected: u32,
+        actual: u32,
+    },
+    InvalidArchive(&'static str),
+    InvalidMarkup(String),
+    Unrepresentable(Vec<char>),
+    TextTooLong {
+        length: usize,
+        maximum: usize,
+    },
+    Normalized {
+        normalizations: Vec<Normalization>,
+        failed: Vec<Normalization>,
+        source: Box<Error>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::UnknownHash(hash) => write!(f, "Unrecognized ROM image (SHA-256: {})", hash),
+            Error::BadSize(size) => write!(f, "Unexpected ROM image size: {} bytes", size),
+            Error::Parse { record, address } => write!(
+                f,
+                "Unable to parse {} record at address ${:06X}",
+                record, address
+            ),
+            Error::UnmappedAddress(address) => {
+                write!(f, "Address ${:06X} is not mapped to the ROM image", address)
+            }
+            Error::UnmappedOffset(offset) => {
+                write!(
+                    f,
+                    "ROM image offset {:06X} is not mapped to an address",
+                    offset
+                )
+            }
+            Error::UnmappedRecord { record, version } => write!(
+                f,
+                "The {} record has not been located in {}",
+                record,
+                rom_map::get_description(*version)
+            ),
+            Error::IndexOutOfRange { record, index } => {
+                write!(f, "Index {} is past the end of the {} table", index, record)
+            }
+            Error::OutOfRange { address, length } => write!(
+                f,
+                "Access of {} bytes at address ${:06X} is out of range",
+                length, address
+            ),
+            Error::InvalidPatch(reason) => write!(f, "Invalid patch: {}", reason),
+            Error::PatchChecksum {
+                kind,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Patch {} checksum mismatch: expected {:08X}, found {:08X}",
+                kind, expected, actual
+            ),
+            Error::InvalidArchive(reason) => write!(f, "Invalid archive: {}", reason),
+            Error::InvalidMarkup(markup) => write!(f, "Invalid text markup: {}", markup),
+            Error::Unrepresentable(chars) => write!(
+                f,
+                "Characters cannot be represented in the game's encoding: {}",
+                chars.iter().collect::<String>()
+            ),
+            Error::TextTooLong { length, maximum } => write!(
+                f,
+                "Encoded text is {} bytes long but at most {} bytes fit",
+                length, maximum
+            ),
+            Error::Normalized {
+                normalizations,
+                failed,
+                source,
+            } => {
+                let join = |steps: &[Normalization]| {
+                    steps
+                        .iter()
+                        .map(|step| step.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+
+                write!(f, "{}", source)?;
+
+                if !normalizations.is_empty() {
+                    write!(f, " (after {})", join(normalizations))?;
+                }
+
+                if !failed.is_empty() {
+                    write!(f, " (no valid internal header after {})", join(failed))?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Normalized { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -0,0 +1,456 @@
+use std::fmt;
+
+use nom::{
+    bytes::complete::take,
+    error::ErrorKind,
+    number::complete::{le_u16, le_u8},
+    IResult,
+};
+
+use crate::map::Facing;
+use crate::rom;
+use crate::rom_map::record::RecordId;
+use crate::Error;
+
+const LAST_PARTY_ACTION: u8 = 0x0F;
+
+const COMMAND_NPC_ACTION: u8 = 0xE0;
+const COMMAND_DIALOGUE: u8 = 0xE1;
+const COMMAND_SET_FLAG: u8 = 0xE2;
+const COMMAND_CLEAR_FLAG: u8 = 0xE3;
+const COMMAND_IF_FLAG_SET: u8 = 0xE4;
+const COMMAND_IF_FLAG_CLEAR: u8 = 0xE5;
+const COMMAND_ELSE: u8 = 0xE6;
+const COMMAND_END_IF: u8 = 0xE7;
+const COMMAND_ADD_CHARACTER: u8 = 0xE8;
+const COMMAND_REMOVE_CHARACTER: u8 = 0xE9;
+const COMMAND_MAP_CHANGE: u8 = 0xEA;
+const COMMAND_MUSIC: u8 = 0xEB;
+const COMMAND_WAIT: u8 = 0xEC;
+const EVENT_END: u8 = 0xFF;
+
+// Branches nest on the parser's stack, so a corrupt script could otherwise recurse without bound.
+const MAXIMUM_DEPTH: usize = 16;
+
+const SPRITE_ACTION: u8 = 0x0F;
+const MAP_CHANGE_X: u8 = 0x3F;
+const MAP_CHANGE_FACING_SHIFT: u8 = 6;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Actor {
+    PartyLeader,
+    Npc(u8),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SpriteAction {
+    Move(Facing),
+    Face(Facing),
+    Hide,
+    Show,
+    Jump,
+    Spin,
+    Pose(u8),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Sprite {
+        actor: Actor,
+        action: SpriteAction,
+    },
+    Dialogue(u16),
+    SetFlag(u8),
+    ClearFlag(u8),
+    If {
+        flag: u8,
+        set: bool,
+        then: Vec<Command>,
+        otherwise: Vec<Command>,
+    },
+    AddCharacter(u8),
+    RemoveCharacter(u8),
+    MapChange {
+        location: u8,
+        x: u8,
+        y: u8,
+        facing: Facing,
+    },
+    Music(u8),
+    Wait(u8),
+    Unknown {
+        code: u8,
+        operands: Vec<u8>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Event {
+    pub commands: Vec<Command>,
+}
+
+impl Event {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let record = rom.record(RecordId::EventData)?;
+        let offset = usize::from(rom.parse_record(RecordId::EventPointer, index, parse_pointer)?);
+        let data = rom.read_bytes(RecordId::EventData, 0)?;
+
+        data.get(offset..)
+            .and_then(|input| parse_event(input).ok())
+            .map(|(_, event)| event)
+            .ok_or(Error::Parse {
+                record: record.name,
+                address: record.address + offset,
+            })
+    }
+
+    /// Returns every event flag the event tests, sets or clears, in order of first reference.
+    pub fn flags(&self) -> Vec<u8> {
+        let mut flags = Vec::new();
+        collect_flags(&self.commands, &mut flags);
+        flags
+    }
+}
+
+fn collect_flags(commands: &[Command], flags: &mut Vec<u8>) {
+    for command in commands {
+        let flag = match command {
+            Command::SetFlag(flag) | Command::ClearFlag(flag) | Command::If { flag, .. } => *flag,
+            _ => continue,
+        };
+
+        if !flags.contains(&flag) {
+            flags.push(flag);
+        }
+
+        if let Command::If {
+            then, otherwise, ..
+        } = command
+        {
+            collect_flags(then, flags);
+            collect_flags(otherwise, flags);
+        }
+    }
+}
+
+pub fn parse_event(input: &[u8]) -> IResult<&[u8], Event> {
+    let (input, (commands, end)) = parse_block(input, 0)?;
+
+    if end != EVENT_END {
+        return Err(nom::Err::Error((input, ErrorKind::MapOpt)));
+    }
+
+    Ok((input, Event { commands }))
+}
+
+// Parses commands up to the end of the event or of a branch, returning the byte that ended them.
+fn parse_block(input: &[u8], depth: usize) -> IResult<&[u8], (Vec<Command>, u8)> {
+    let mut input = input;
+    let mut commands = Vec::new();
+
+    loop {
+        let (rest, code) = le_u8(input)?;
+        let (rest, operands) = take(operand_length(code))(rest)?;
+
+        let (rest, command) = match code {
+            EVENT_END | COMMAND_ELSE | COMMAND_END_IF => return Ok((rest, (commands, code))),
+            0..=LAST_PARTY_ACTION => (
+                rest,
+                Command::Sprite {
+                    actor: Actor::PartyLeader,
+                    action: decode_sprite_action(code),
+                },
+            ),
+            COMMAND_NPC_ACTION => (
+                rest,
+                Command::Sprite {
+                    actor: Actor::Npc(operands[0]),
+                    action: decode_sprite_action(operands[1]),
+                },
+            ),
+            COMMAND_DIALOGUE => (
+                rest,
+                Command::Dialogue(u16::from(operands[0]) | (u16::from(operands[1]) << 8)),
+            ),
+            COMMAND_SET_FLAG => (rest, Command::SetFlag(operands[0])),
+            COMMAND_CLEAR_FLAG => (rest, Command::ClearFlag(operands[0])),
+            COMMAND_IF_FLAG_SET | COMMAND_IF_FLAG_CLEAR => {
+                if depth >= MAXIMUM_DEPTH {
+                    return Err(nom::Err::Error((input, ErrorKind::TooLarge)));
+                }
+
+                let (rest, (then, end)) = parse_block(rest, depth + 1)?;
+
+                let (rest, otherwise) = match end {
+                    COMMAND_END_IF => (rest, Vec::new()),
+                    COMMAND_ELSE => match parse_block(rest, depth + 1)? {
+                        (rest, (otherwise, COMMAND_END_IF)) => (rest, otherwise),
+                        (rest, _) => return Err(nom::Err::Error((rest, ErrorKind::MapOpt))),
+                    },
+                    _ => return Err(nom::Err::Error((rest, ErrorKind::MapOpt))),
+                };
+
+                (
+                    rest,
+                    Command::If {
+                        flag: operands[0],
+                        set: code == COMMAND_IF_FLAG_SET,
+                        then,
+                        otherwise,
+                    },
+                )
+            }
+            COMMAND_ADD_CHARACTER => (rest, Command::AddCharacter(operands[0])),
+            COMMAND_REMOVE_CHARACTER => (rest, Command::RemoveCharacter(operands[0])),
+            COMMAND_MAP_CHANGE => (
+                rest,
+                Command::MapChange {
+                    location: operands[0],
+                    x: operands[1] & MAP_CHANGE_X,
+                    y: operands[2],
+                    facing: Facing::new(operands[1] >> MAP_CHANGE_FACING_SHIFT),
+                },
+            ),
+            COMMAND_MUSIC => (rest, Command::Music(operands[0])),
+            COMMAND_WAIT => (rest, Command::Wait(operands[0])),
+            _ => (
+                rest,
+                Command::Unknown {
+                    code,
+                    operands: operands.to_vec(),
+                },
+            ),
+        };
+
+        commands.push(command);
+        input = rest;
+    }
+}
+
+// Every opcode has a fixed number of operands, so commands the parser doesn't decode can still be
+// stepped over.
+fn operand_length(code: u8) -> usize {
+    match code {
+        0x00..=0xDF | COMMAND_ELSE | COMMAND_END_IF | EVENT_END => 0,
+        COMMAND_SET_FLAG
+        | COMMAND_CLEAR_FLAG
+        | COMMAND_IF_FLAG_SET
+        | COMMAND_IF_FLAG_CLEAR
+        | COMMAND_ADD_CHARACTER
+        | COMMAND_REMOVE_CHARACTER
+        | COMMAND_MUSIC
+        | COMMAND_WAIT
+        | 0xED..=0xFD => 1,
+        COMMAND_NPC_ACTION | COMMAND_DIALOGUE => 2,
+        COMMAND_MAP_CHANGE => 3,
+        0xFE => 4,
+    }
+}
+
+pub fn decode_sprite_action(value: u8) -> SpriteAction {
+    match value & SPRITE_ACTION {
+        action @ 0x00..=0x03 => SpriteAction::Move(Facing::new(action)),
+        action @ 0x04..=0x07 => SpriteAction::Face(Facing::new(action)),
+        0x08 => SpriteAction::Hide,
+        0x09 => SpriteAction::Show,
+        0x0A => SpriteAction::Jump,
+        0x0B => SpriteAction::Spin,
+        action => SpriteAction::Pose(action - 0x0C),
+    }
+}
+
+fn parse_pointer(input: &[u8]) -> IResult<&[u8], u16> {
+    le_u16(input)
+}
+
+impl fmt::Display for Actor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Actor::PartyLeader => write!(f, "party leader"),
+            Actor::Npc(npc) => write!(f, "NPC {}", npc),
+        }
+    }
+}
+
+impl fmt::Display for SpriteAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpriteAction::Move(facing) => write!(f, "move {}", facing),
+            SpriteAction::Face(facing) => write!(f, "face {}", facing),
+            SpriteAction::Hide => write!(f, "hide"),
+            SpriteAction::Show => write!(f, "show"),
+            SpriteAction::Jump => write!(f, "jump"),
+            SpriteAction::Spin => write!(f, "spin"),
+            SpriteAction::Pose(pose) => write!(f, "pose {}", pose),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Sprite { actor, action } => write!(f, "{}: {}", actor, action),
+            Command::Dialogue(dialogue) => write!(f, "show dialogue ${:04X}", dialogue),
+            Command::SetFlag(flag) => write!(f, "set flag ${:02X}", flag),
+            Command::ClearFlag(flag) => write!(f, "clear flag ${:02X}", flag),
+            Command::If { flag, set, .. } => {
+                let state = if *set { "set" } else { "clear" };
+                write!(f, "if flag ${:02X} is {}", flag, state)
+            }
+            Command::AddCharacter(character) => {
+                write!(f, "add character ${:02X} to party", character)
+            }
+            Command::RemoveCharacter(character) => {
+                write!(f, "remove character ${:02X} from party", character)
+            }
+            Command::MapChange {
+                location,
+                x,
+                y,
+                facing,
+            } => write!(
+                f,
+                "go to location ${:02X} at ({}, {}) facing {}",
+                location, x, y, facing
+            ),
+            Command::Music(song) => write!(f, "play song ${:02X}", song),
+            Command::Wait(frames) => write!(f, "wait {} frames", frames),
+            Command::Unknown { code, operands } => {
+                write!(f, "command ${:02X}", code)?;
+                for operand in operands {
+                    write!(f, " ${:02X}", operand)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn write_commands(f: &mut fmt::Formatter, commands: &[Command], depth: usize) -> fmt::Result {
+    let indent = "    ".repeat(depth);
+
+    for command in commands {
+        match command {
+            Command::If {
+                then, otherwise, ..
+            } => {
+                writeln!(f, "{}{}:", indent, command)?;
+                write_commands(f, then, depth + 1)?;
+
+                if !otherwise.is_empty() {
+                    writeln!(f, "{}else:", indent)?;
+                    write_commands(f, otherwise, depth + 1)?;
+                }
+            }
+            _ => writeln!(f, "{}{}", indent, command)?,
+        }
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_commands(f, &self.commands, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_parse_event() {
+        assert_eq!(
+            parse_event(&[
+                0x02, 0xE0, 0x03, 0x0D, 0xE4, 0x10, 0xE1, 0x34, 0x12, 0xE6, 0xE2, 0x10, 0xE7, 0xFF,
+                0x99,
+            ]),
+            Ok((
+                &[0x99][..],
+                Event {
+                    commands: vec![
+                        Command::Sprite {
+                            actor: Actor::PartyLeader,
+                            action: SpriteAction::Move(Facing::Down),
+                        },
+                        Command::Sprite {
+                            actor: Actor::Npc(3),
+                            action: SpriteAction::Pose(1),
+                        },
+                        Command::If {
+                            flag: 0x10,
+                            set: true,
+                            then: vec![Command::Dialogue(0x1234)],
+                            otherwise: vec![Command::SetFlag(0x10)],
+                        },
+                    ],
+                }
+            ))
+        );
+
+        assert!(parse_event(&[0xE5, 0x10, 0xE2, 0x10, 0xFF]).is_err());
+        assert!(parse_event(&[0x02, 0xE7]).is_err());
+        assert!(parse_event(&[0x02, 0x03]).is_err());
+        assert_eq!(
+            parse_event(&[0x20, 0xFE, 0x01, 0x02, 0x03, 0x04, 0xFF]),
+            Ok((
+                &[][..],
+                Event {
+                    commands: vec![
+                        Command::Unknown {
+                            code: 0x20,
+                            operands: vec![],
+                        },
+                        Command::Unknown {
+                            code: 0xFE,
+                            operands: vec![0x01, 0x02, 0x03, 0x04],
+                        },
+                    ],
+                }
+            ))
+        );
+        assert!(parse_event(&[0xFE, 0x01, 0x02, 0xFF]).is_err());
+        assert!(parse_event(&[0xEA, 0x07, 0x4A]).is_err());
+    }
+
+    #[test]
+    fn test_parse_event_depth() {
+        let nested = |depth: usize| {
+            let mut bytes = [0xE4, 0x01].repeat(depth);
+            bytes.extend(vec![0xE7; depth]);
+            bytes.push(0xFF);
+            bytes
+        };
+
+        assert!(parse_event(&nested(MAXIMUM_DEPTH)).is_ok());
+        assert!(parse_event(&nested(MAXIMUM_DEPTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_event_new() {
+        let mut data = vec![0; 0x10];
+        data.extend_from_slice(&[
+            0xE5, 0x21, 0xE8, 0x04, 0xE3, 0x05, 0xE7, 0xEA, 0x07, 0x4A, 0x0C, 0xEB, 0x02, 0xF0,
+            0x33, 0xFF,
+        ]);
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::EventPointer, 9, &[0x10, 0x00])
+            .write(RecordId::EventData, 0, &data)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let event = Event::new(&rom, 9).unwrap();
+
+        assert_eq!(event.flags(), vec![0x21, 0x05]);
+        assert_eq!(
+            event.to_string(),
+            "if flag $21 is clear:\n    add character $04 to party\n    clear flag $05\n\
+             go to location $07 at (10, 12) facing right\nplay song $02\ncommand $F0 $33\n"
+        );
+    }
+}
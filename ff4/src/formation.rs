@@ -0,0 +1,324 @@
+use nom::{
+    number::complete::{le_u16, le_u8},
+    IResult,
+};
+
+use crate::map::OutdoorMap;
+use crate::rom;
+use crate::rom_map::record::RecordId;
+use crate::Error;
+
+pub const ENCOUNTERS_PER_GROUP: usize = 8;
+
+// The chance, out of 256, of each slot in an encounter group being chosen.
+pub const ENCOUNTER_PROBABILITIES: [u16; ENCOUNTERS_PER_GROUP] = [43, 43, 43, 43, 30, 30, 16, 8];
+
+const FLAGS_BACK_ATTACK: u8 = 0x01;
+const FLAGS_BOSS_MUSIC: u8 = 0x02;
+const FLAGS_NO_ESCAPE: u8 = 0x04;
+
+const NO_MONSTER: u8 = 0xFF;
+const MONSTER_GROUPS: usize = 3;
+
+const REGION_SIZE: usize = 32;
+const REGIONS_PER_ROW: usize = 8;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MonsterGroup {
+    pub monster: u8,
+    pub count: u8,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FormationMonster {
+    pub monster: u8,
+    pub x: u8,
+    pub y: u8,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Formation {
+    pub groups: Vec<MonsterGroup>,
+    pub monsters: Vec<FormationMonster>,
+    pub back_attack: bool,
+    pub boss_music: bool,
+    pub no_escape: bool,
+    pub arrangement: u8,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct FormationRecord {
+    flags: u8,
+    groups: Vec<MonsterGroup>,
+    arrangement: u8,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Encounter {
+    pub formation: u16,
+    pub probability: u16,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct EncounterGroup {
+    pub encounters: Vec<Encounter>,
+}
+
+impl Formation {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let record = rom.parse_record(RecordId::Formation, index, parse_formation)?;
+        let arrangement =
+            rom.read_bytes(RecordId::FormationArrangement, record.arrangement.into())?;
+
+        let count: usize = record
+            .groups
+            .iter()
+            .map(|group| usize::from(group.count))
+            .sum();
+        if count > arrangement.len() {
+            return Err(Error::Parse {
+                record: rom.record(RecordId::Formation)?.name,
+                address: rom.record_address(RecordId::Formation, index)?,
+            });
+        }
+
+        let monsters = record
+            .groups
+            .iter()
+            .flat_map(|group| (0..group.count).map(move |_| group.monster))
+            .zip(arrangement.iter())
+            .map(|(monster, &position)| FormationMonster {
+                monster,
+                x: position >> 4,
+                y: position & 0x0F,
+            })
+            .collect();
+
+        Ok(Formation {
+            groups: record.groups,
+            monsters,
+            back_attack: record.flags & FLAGS_BACK_ATTACK > 0,
+            boss_music: record.flags & FLAGS_BOSS_MUSIC > 0,
+            no_escape: record.flags & FLAGS_NO_ESCAPE > 0,
+            arrangement: record.arrangement,
+        })
+    }
+}
+
+impl EncounterGroup {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        rom.parse_record(RecordId::EncounterGroup, index, parse_encounter_group)
+    }
+
+    /// Returns the encounter group for the region of an outdoor map containing a tile.
+    pub fn new_outdoor(rom: &rom::Rom, map: OutdoorMap, x: usize, y: usize) -> Result<Self, Error> {
+        let regions = rom.read_bytes(RecordId::EncounterRegion, map as usize)?;
+        let region = (y / REGION_SIZE) * REGIONS_PER_ROW + x / REGION_SIZE;
+        let record = rom.record(RecordId::EncounterRegion)?;
+
+        let group = regions.get(region).copied().ok_or(Error::Parse {
+            record: record.name,
+            address: rom.record_address(RecordId::EncounterRegion, map as usize)? + region,
+        })?;
+
+        EncounterGroup::new(rom, group.into())
+    }
+
+    pub fn new_location(rom: &rom::Rom, location: usize) -> Result<Self, Error> {
+        let group = rom.read_bytes(RecordId::LocationEncounterGroup, location)?[0];
+        EncounterGroup::new(rom, group.into())
+    }
+}
+
+pub(crate) fn parse_formation(input: &[u8]) -> IResult<&[u8], FormationRecord> {
+    let (input, flags) = le_u8(input)?;
+    let (input, monster_1) = le_u8(input)?;
+    let (input, monster_2) = le_u8(input)?;
+    let (input, monster_3) = le_u8(input)?;
+    let (input, counts_1_2) = le_u8(input)?;
+    let (input, counts_3) = le_u8(input)?;
+    let (input, arrangement) = le_u8(input)?;
+    let (input, _) = le_u8(input)?; // unused
+
+    let monsters: [u8; MONSTER_GROUPS] = [monster_1, monster_2, monster_3];
+    let counts: [u8; MONSTER_GROUPS] = [counts_1_2 >> 4, counts_1_2 & 0x0F, counts_3 >> 4];
+
+    let groups = monsters
+        .iter()
+        .zip(counts.iter())
+        .filter(|(&monster, &count)| monster != NO_MONSTER && count > 0)
+        .map(|(&monster, &count)| MonsterGroup { monster, count })
+        .collect();
+
+    Ok((
+        input,
+        FormationRecord {
+            flags,
+            groups,
+            arrangement,
+        },
+    ))
+}
+
+pub fn parse_encounter_group(input: &[u8]) -> IResult<&[u8], EncounterGroup> {
+    let mut input = input;
+    let mut encounters = Vec::with_capacity(ENCOUNTERS_PER_GROUP);
+
+    for &probability in ENCOUNTER_PROBABILITIES.iter() {
+        let (rest, formation) = le_u16(input)?;
+        encounters.push(Encounter {
+            formation,
+            probability,
+        });
+        input = rest;
+    }
+
+    Ok((input, EncounterGroup { encounters }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_parse_formation() {
+        assert_eq!(
+            parse_formation(&[0x05, 0x10, 0x11, 0xFF, 0x21, 0x30, 0x02, 0x00]),
+            Ok((
+                &[][..],
+                FormationRecord {
+                    flags: 0x05,
+                    groups: vec![
+                        MonsterGroup {
+                            monster: 0x10,
+                            count: 2,
+                        },
+                        MonsterGroup {
+                            monster: 0x11,
+                            count: 1,
+                        },
+                    ],
+                    arrangement: 2,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_encounter_probabilities() {
+        assert_eq!(ENCOUNTER_PROBABILITIES.iter().sum::<u16>(), 256);
+    }
+
+    #[test]
+    fn test_formation_new() {
+        let rom = RomBuilder::new(Version::Us)
+            .write(
+                RecordId::Formation,
+                7,
+                &[0x02, 0x20, 0x21, 0x22, 0x12, 0x00, 0x03, 0x00],
+            )
+            .write(
+                RecordId::FormationArrangement,
+                3,
+                &[0x11, 0x22, 0x33, 0x44, 0x00, 0x00, 0x00, 0x00],
+            )
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let formation = Formation::new(&rom, 7).unwrap();
+
+        assert!(formation.boss_music);
+        assert!(!formation.back_attack && !formation.no_escape);
+        assert_eq!(formation.groups.len(), 2);
+        assert_eq!(
+            formation.monsters,
+            [
+                FormationMonster {
+                    monster: 0x20,
+                    x: 1,
+                    y: 1,
+                },
+                FormationMonster {
+                    monster: 0x21,
+                    x: 2,
+                    y: 2,
+                },
+                FormationMonster {
+                    monster: 0x21,
+                    x: 3,
+                    y: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_formation_new_too_many_monsters() {
+        let rom = RomBuilder::new(Version::Us)
+            .write(
+                RecordId::Formation,
+                1,
+                &[0x00, 0x20, 0x21, 0x22, 0x45, 0x00, 0x00, 0x00],
+            )
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            Formation::new(&rom, 1),
+            Err(Error::Parse {
+                address: 0x0EDB08,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_encounter_group_new() {
+        let mut regions = [0; 0x40];
+        regions[9] = 4;
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::EncounterRegion, 1, &regions)
+            .write(
+                RecordId::EncounterGroup,
+                4,
+                &[
+                    0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x04, 0x00, 0x05, 0x00, 0x06, 0x00, 0x07,
+                    0x00, 0x08, 0x01,
+                ],
+            )
+            .write(RecordId::LocationEncounterGroup, 0x20, &[0x04])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let group = EncounterGroup::new_outdoor(&rom, OutdoorMap::Underworld, 40, 32).unwrap();
+
+        assert_eq!(group.encounters.len(), ENCOUNTERS_PER_GROUP);
+        assert_eq!(
+            group.encounters[7],
+            Encounter {
+                formation: 0x108,
+                probability: 8,
+            }
+        );
+        assert_eq!(EncounterGroup::new_location(&rom, 0x20).unwrap(), group);
+        assert_eq!(
+            EncounterGroup::new_outdoor(&rom, OutdoorMap::Overworld, 40, 32)
+                .unwrap()
+                .encounters[0]
+                .formation,
+            0
+        );
+
+        // Tiles past the edge of the map have no region.
+        assert!(matches!(
+            EncounterGroup::new_outdoor(&rom, OutdoorMap::Underworld, 0x100, 0x100),
+            Err(Error::Parse { .. })
+        ));
+    }
+}
@@ -1,13 +1,17 @@
 use image::Rgba;
 
 use crate::rom;
-use crate::rom_map;
+use crate::rom_map::record::RecordId;
+use crate::Error;
 
 pub const BYTES_PER_TILE_3BPP: usize = 8 * 8 * 3 / 8;
 pub const BYTES_PER_TILE_4BPP: usize = 8 * 8 / 2;
 pub const BYTES_PER_TILE_UNPACKED: usize = 8 * 8;
 
 pub const FIELD_SPRITE_PLAYER_FRAME_COUNT: usize = 16;
+pub const FIELD_SPRITE_NPC_FRAME_COUNT: usize = 8;
+/// The NPC sprite sheets run up to the player sheets, which leaves room for this many.
+pub const FIELD_SPRITE_NPC_SHEET_COUNT: usize = 0x55;
 
 #[derive(Copy, Clone, Debug)]
 pub struct TileReference {
@@ -52,35 +56,69 @@ pub struct FieldSpriteSheet {
 }
 
 impl FieldSpriteSheet {
-    pub fn new_player(rom: &rom::Rom, index: usize) -> Self {
-        let record = rom_map::record::FIELD_SPRITE_SHEET_PLAYER;
-        let bytes = rom.read_bytes(record, index);
+    pub fn new_player(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        Self::new(
+            rom,
+            index,
+            RecordId::FieldSpriteSheetPlayer,
+            RecordId::FieldSpriteCompositionPlayer,
+            RecordId::FieldSpritePaletteIndexPlayer,
+            FIELD_SPRITE_PLAYER_FRAME_COUNT,
+        )
+    }
+
+    pub fn new_npc(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        Self::new(
+            rom,
+            index,
+            RecordId::FieldSpriteSheetNpc,
+            RecordId::FieldSpriteCompositionNpc,
+            RecordId::FieldSpritePaletteIndexNpc,
+            FIELD_SPRITE_NPC_FRAME_COUNT,
+        )
+    }
 
-        let tiles = (0..bytes.len() / 24)
-            .map(|i| parse_tile_3bpp(&bytes[i * 24..(i + 1) * 24]))
+    fn new(
+        rom: &rom::Rom,
+        index: usize,
+        sheet_id: RecordId,
+        composition_id: RecordId,
+        palette_index_id: RecordId,
+        frame_count: usize,
+    ) -> Result<Self, Error> {
+        let bytes = rom.read_bytes(sheet_id, index)?;
+
+        let tiles = bytes
+            .chunks_exact(BYTES_PER_TILE_3BPP)
+            .map(parse_tile_3bpp)
             .collect();
 
-        let composition = (0..FIELD_SPRITE_PLAYER_FRAME_COUNT)
+        let composition = (0..frame_count)
             .map(|i| {
-                SpriteComposition::new(
-                    rom.read_bytes(rom_map::record::FIELD_SPRITE_COMPOSITION_PLAYER, i),
-                )
+                rom.read_bytes(composition_id, i)
+                    .map(SpriteComposition::new)
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
-        let record = rom_map::record::FIELD_SPRITE_PALETTE_INDEX_PLAYER;
-        let palette_index = usize::from(rom.read_u8(record.address + index));
+        let palette_index = usize::from(rom.read_bytes(palette_index_id, index)?[0]);
 
-        Self {
+        Ok(Self {
             composition,
             tiles,
             palette_index,
-        }
+        })
     }
 }
 
-pub fn get_field_sprite_palette_player(rom: &rom::Rom, index: usize) -> Vec<Rgba<u8>> {
-    rom.read_palette(rom_map::record::FIELD_SPRITE_PALETTE_PLAYER, index, 1)
+pub fn get_field_sprite_palette_player(
+    rom: &rom::Rom,
+    index: usize,
+) -> Result<Vec<Rgba<u8>>, Error> {
+    rom.read_palette(RecordId::FieldSpritePalettePlayer, index, 1)
+}
+
+pub fn get_field_sprite_palette_npc(rom: &rom::Rom, index: usize) -> Result<Vec<Rgba<u8>>, Error> {
+    rom.read_palette(RecordId::FieldSpritePaletteNpc, index, 1)
 }
 
 pub fn parse_tile_3bpp(data: &[u8]) -> Vec<u8> {
@@ -118,3 +156,84 @@ pub fn parse_tile_4bpp(data: &[u8]) -> Vec<u8> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_field_sprite_sheet_new_player() {
+        let mut tile = [0; BYTES_PER_TILE_3BPP];
+        tile[0] = 0xFF;
+        tile[16] = 0x80;
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::FieldSpriteSheetPlayer, 2, &tile)
+            .write(
+                RecordId::FieldSpriteCompositionPlayer,
+                1,
+                &[0x00, 0x40, 0x01, 0x80, 0x02, 0x00, 0x03, 0xC0],
+            )
+            .write(RecordId::FieldSpritePaletteIndexPlayer, 2, &[5])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let sheet = FieldSpriteSheet::new_player(&rom, 2).unwrap();
+
+        assert_eq!(sheet.tiles.len(), 32);
+        assert_eq!(sheet.tiles[0][0..8], [5, 1, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(sheet.tiles[1], vec![0; BYTES_PER_TILE_UNPACKED]);
+
+        assert_eq!(sheet.composition.len(), FIELD_SPRITE_PLAYER_FRAME_COUNT);
+        assert_eq!(sheet.composition[1].upper_left.tile, 0);
+        assert!(sheet.composition[1].upper_left.hflip);
+        assert!(!sheet.composition[1].upper_left.vflip);
+        assert!(sheet.composition[1].upper_right.vflip);
+        assert_eq!(sheet.composition[1].lower_left.tile, 2);
+        assert!(sheet.composition[1].lower_right.hflip);
+        assert!(sheet.composition[1].lower_right.vflip);
+
+        assert_eq!(sheet.palette_index, 5);
+    }
+
+    #[test]
+    fn test_field_sprite_sheet_new_npc() {
+        let mut tile = [0; BYTES_PER_TILE_3BPP];
+        tile[1] = 0x01;
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::FieldSpriteSheetNpc, 3, &tile)
+            .write(
+                RecordId::FieldSpriteCompositionNpc,
+                7,
+                &[0x04, 0x00, 0x05, 0x00, 0x06, 0x00, 0x07, 0x40],
+            )
+            .write(RecordId::FieldSpritePaletteIndexNpc, 3, &[2])
+            .write(RecordId::FieldSpritePaletteNpc, 2, &[0x00, 0x7C])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let sheet = FieldSpriteSheet::new_npc(&rom, 3).unwrap();
+
+        assert_eq!(sheet.tiles.len(), 16);
+        assert_eq!(sheet.tiles[0][7], 2);
+        assert_eq!(sheet.composition.len(), FIELD_SPRITE_NPC_FRAME_COUNT);
+        assert_eq!(sheet.composition[7].lower_right.tile, 7);
+        assert!(sheet.composition[7].lower_right.hflip);
+        assert_eq!(sheet.palette_index, 2);
+
+        let palette = get_field_sprite_palette_npc(&rom, sheet.palette_index).unwrap();
+        assert_eq!(palette.len(), 8);
+        assert_eq!(palette[0], Rgba([0, 0, 255, 255]));
+
+        assert!(FieldSpriteSheet::new_npc(&rom, FIELD_SPRITE_NPC_SHEET_COUNT - 1).is_ok());
+        assert!(matches!(
+            FieldSpriteSheet::new_npc(&rom, FIELD_SPRITE_NPC_SHEET_COUNT),
+            Err(Error::IndexOutOfRange { .. })
+        ));
+    }
+}
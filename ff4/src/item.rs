@@ -0,0 +1,453 @@
+use nom::{
+    number::complete::{le_u16, le_u8},
+    IResult,
+};
+
+use crate::rom;
+use crate::rom_map::record::RecordId;
+use crate::text;
+use crate::Error;
+
+pub const WEAPON_COUNT: usize = 0x61;
+pub const ARMOR_COUNT: usize = 0x4F;
+pub const CONSUMABLE_COUNT: usize = 0x50;
+
+pub const FIRST_ARMOR: usize = WEAPON_COUNT;
+pub const FIRST_CONSUMABLE: usize = FIRST_ARMOR + ARMOR_COUNT;
+
+const WEAPON_FLAGS_LONG_RANGE: u8 = 0x80;
+const WEAPON_FLAGS_TWO_HANDED: u8 = 0x40;
+const WEAPON_FLAGS_THROWABLE: u8 = 0x20;
+const WEAPON_FLAGS_USABLE: u8 = 0x10;
+
+const ARMOR_FLAGS_KIND: u8 = 0x03;
+
+const CONSUMABLE_FLAGS_BATTLE: u8 = 0x80;
+const CONSUMABLE_FLAGS_FIELD: u8 = 0x40;
+const CONSUMABLE_FLAGS_THROWABLE: u8 = 0x20;
+
+const STAT_BONUS_STRENGTH: u8 = 0x80;
+const STAT_BONUS_AGILITY: u8 = 0x40;
+const STAT_BONUS_VITALITY: u8 = 0x20;
+const STAT_BONUS_WISDOM: u8 = 0x10;
+const STAT_BONUS_WILL: u8 = 0x08;
+const STAT_BONUS_AMOUNT: u8 = 0x07;
+const STAT_BONUS_AMOUNTS: [i8; 8] = [0, 3, 5, 10, 15, -5, -10, -15];
+
+const PRICE_THOUSANDS: u8 = 0x80;
+
+/// The shared element, race and status entry referenced by every kind of item.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Attributes {
+    pub element: u8,
+    pub race: u8,
+    pub status: u16,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct StatBonus {
+    pub strength: i8,
+    pub agility: i8,
+    pub vitality: i8,
+    pub wisdom: i8,
+    pub will: i8,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ArmorKind {
+    Shield,
+    Helmet,
+    Body,
+    Accessory,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Weapon {
+    pub name: String,
+    pub price: u32,
+    pub long_range: bool,
+    pub two_handed: bool,
+    pub throwable: bool,
+    pub usable: bool,
+    pub attack: u8,
+    pub hit_rate: u8,
+    pub spell: u8,
+    pub attributes: Attributes,
+    pub equip_classes: u16,
+    pub stat_bonus: StatBonus,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Armor {
+    pub name: String,
+    pub price: u32,
+    pub kind: ArmorKind,
+    pub defense: u8,
+    pub evasion: u8,
+    pub magic_defense: u8,
+    pub magic_evasion: u8,
+    pub attributes: Attributes,
+    pub equip_classes: u16,
+    pub stat_bonus: StatBonus,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Item {
+    pub name: String,
+    pub price: u32,
+    pub usable_in_battle: bool,
+    pub usable_in_field: bool,
+    pub throwable: bool,
+    pub spell: u8,
+    pub attributes: Attributes,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct WeaponRecord {
+    flags: u8,
+    attack: u8,
+    hit_rate: u8,
+    spell: u8,
+    attributes: u8,
+    equip: u8,
+    stat_bonus: StatBonus,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ArmorRecord {
+    flags: u8,
+    defense: u8,
+    evasion: u8,
+    magic_defense: u8,
+    magic_evasion: u8,
+    attributes: u8,
+    equip: u8,
+    stat_bonus: StatBonus,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ConsumableRecord {
+    flags: u8,
+    spell: u8,
+    attributes: u8,
+}
+
+impl Weapon {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let record = rom.parse_record(RecordId::ItemWeapon, index, parse_weapon)?;
+
+        Ok(Weapon {
+            name: get_name(rom, index)?,
+            price: get_price(rom, index)?,
+            long_range: record.flags & WEAPON_FLAGS_LONG_RANGE > 0,
+            two_handed: record.flags & WEAPON_FLAGS_TWO_HANDED > 0,
+            throwable: record.flags & WEAPON_FLAGS_THROWABLE > 0,
+            usable: record.flags & WEAPON_FLAGS_USABLE > 0,
+            attack: record.attack,
+            hit_rate: record.hit_rate,
+            spell: record.spell,
+            attributes: Attributes::new(rom, record.attributes.into())?,
+            equip_classes: get_equip_classes(rom, record.equip.into())?,
+            stat_bonus: record.stat_bonus,
+        })
+    }
+}
+
+impl Armor {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let record = rom.parse_record(RecordId::ItemArmor, index, parse_armor)?;
+
+        let kind = match record.flags & ARMOR_FLAGS_KIND {
+            0 => ArmorKind::Shield,
+            1 => ArmorKind::Helmet,
+            2 => ArmorKind::Body,
+            _ => ArmorKind::Accessory,
+        };
+
+        Ok(Armor {
+            name: get_name(rom, FIRST_ARMOR + index)?,
+            price: get_price(rom, FIRST_ARMOR + index)?,
+            kind,
+            defense: record.defense,
+            evasion: record.evasion,
+            magic_defense: record.magic_defense,
+            magic_evasion: record.magic_evasion,
+            attributes: Attributes::new(rom, record.attributes.into())?,
+            equip_classes: get_equip_classes(rom, record.equip.into())?,
+            stat_bonus: record.stat_bonus,
+        })
+    }
+}
+
+impl Item {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let record = rom.parse_record(RecordId::ItemConsumable, index, parse_consumable)?;
+
+        Ok(Item {
+            name: get_name(rom, FIRST_CONSUMABLE + index)?,
+            price: get_price(rom, FIRST_CONSUMABLE + index)?,
+            usable_in_battle: record.flags & CONSUMABLE_FLAGS_BATTLE > 0,
+            usable_in_field: record.flags & CONSUMABLE_FLAGS_FIELD > 0,
+            throwable: record.flags & CONSUMABLE_FLAGS_THROWABLE > 0,
+            spell: record.spell,
+            attributes: Attributes::new(rom, record.attributes.into())?,
+        })
+    }
+}
+
+impl Attributes {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        rom.parse_record(RecordId::ItemAttributes, index, parse_attributes)
+    }
+}
+
+/// Returns the name of any item by its id, where weapons come first, then armor, then
+/// consumables.
+pub fn get_name(rom: &rom::Rom, id: usize) -> Result<String, Error> {
+    text::read_name(rom, RecordId::TextItemName, id)
+}
+
+pub fn get_price(rom: &rom::Rom, id: usize) -> Result<u32, Error> {
+    Ok(decode_price(rom.read_bytes(RecordId::ItemPrice, id)?[0]))
+}
+
+pub fn get_equip_classes(rom: &rom::Rom, index: usize) -> Result<u16, Error> {
+    rom.parse_record(RecordId::ItemEquipClasses, index, parse_equip_classes)
+}
+
+pub fn decode_price(value: u8) -> u32 {
+    let multiplier = if value & PRICE_THOUSANDS > 0 {
+        1000
+    } else {
+        10
+    };
+    u32::from(value & !PRICE_THOUSANDS) * multiplier
+}
+
+pub(crate) fn parse_weapon(input: &[u8]) -> IResult<&[u8], WeaponRecord> {
+    let (input, flags) = le_u8(input)?;
+    let (input, attack) = le_u8(input)?;
+    let (input, hit_rate) = le_u8(input)?;
+    let (input, spell) = le_u8(input)?;
+    let (input, attributes) = le_u8(input)?;
+    let (input, equip) = le_u8(input)?;
+    let (input, stat_bonus) = parse_stat_bonus(input)?;
+    let (input, _) = le_u8(input)?; // graphics
+
+    Ok((
+        input,
+        WeaponRecord {
+            flags,
+            attack,
+            hit_rate,
+            spell,
+            attributes,
+            equip,
+            stat_bonus,
+        },
+    ))
+}
+
+pub(crate) fn parse_armor(input: &[u8]) -> IResult<&[u8], ArmorRecord> {
+    let (input, flags) = le_u8(input)?;
+    let (input, defense) = le_u8(input)?;
+    let (input, evasion) = le_u8(input)?;
+    let (input, magic_defense) = le_u8(input)?;
+    let (input, magic_evasion) = le_u8(input)?;
+    let (input, attributes) = le_u8(input)?;
+    let (input, equip) = le_u8(input)?;
+    let (input, stat_bonus) = parse_stat_bonus(input)?;
+
+    Ok((
+        input,
+        ArmorRecord {
+            flags,
+            defense,
+            evasion,
+            magic_defense,
+            magic_evasion,
+            attributes,
+            equip,
+            stat_bonus,
+        },
+    ))
+}
+
+pub(crate) fn parse_consumable(input: &[u8]) -> IResult<&[u8], ConsumableRecord> {
+    let (input, flags) = le_u8(input)?;
+    let (input, spell) = le_u8(input)?;
+    let (input, attributes) = le_u8(input)?;
+    let (input, _) = le_u8(input)?; // unused
+
+    Ok((
+        input,
+        ConsumableRecord {
+            flags,
+            spell,
+            attributes,
+        },
+    ))
+}
+
+pub fn parse_attributes(input: &[u8]) -> IResult<&[u8], Attributes> {
+    let (input, element) = le_u8(input)?;
+    let (input, race) = le_u8(input)?;
+    let (input, status) = le_u16(input)?;
+
+    Ok((
+        input,
+        Attributes {
+            element,
+            race,
+            status,
+        },
+    ))
+}
+
+pub fn parse_equip_classes(input: &[u8]) -> IResult<&[u8], u16> {
+    le_u16(input)
+}
+
+fn parse_stat_bonus(input: &[u8]) -> IResult<&[u8], StatBonus> {
+    let (input, value) = le_u8(input)?;
+    let amount = STAT_BONUS_AMOUNTS[usize::from(value & STAT_BONUS_AMOUNT)];
+    let bonus = |flag: u8| if value & flag > 0 { amount } else { 0 };
+
+    Ok((
+        input,
+        StatBonus {
+            strength: bonus(STAT_BONUS_STRENGTH),
+            agility: bonus(STAT_BONUS_AGILITY),
+            vitality: bonus(STAT_BONUS_VITALITY),
+            wisdom: bonus(STAT_BONUS_WISDOM),
+            will: bonus(STAT_BONUS_WILL),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_parse_weapon() {
+        assert_eq!(
+            parse_weapon(&[0xC0, 0x30, 0x63, 0x00, 0x05, 0x02, 0xC2, 0x11]),
+            Ok((
+                &[][..],
+                WeaponRecord {
+                    flags: 0xC0,
+                    attack: 48,
+                    hit_rate: 99,
+                    spell: 0,
+                    attributes: 5,
+                    equip: 2,
+                    stat_bonus: StatBonus {
+                        strength: 5,
+                        agility: 5,
+                        ..StatBonus::default()
+                    },
+                }
+            ))
+        );
+        assert!(parse_weapon(&[0xC0, 0x30, 0x63, 0x00, 0x05, 0x02, 0xC2]).is_err());
+    }
+
+    #[test]
+    fn test_decode_price() {
+        assert_eq!(decode_price(0x0F), 150);
+        assert_eq!(decode_price(0x8A), 10000);
+    }
+
+    #[test]
+    fn test_weapon_new() {
+        let rom = RomBuilder::new(Version::Us)
+            .write(
+                RecordId::ItemWeapon,
+                3,
+                &[0x40, 0x1E, 0x50, 0x00, 0x02, 0x01, 0x1A, 0x00],
+            )
+            .write(RecordId::ItemAttributes, 2, &[0x02, 0x10, 0x00, 0x40])
+            .write(RecordId::ItemEquipClasses, 1, &[0x81, 0x00])
+            .write(RecordId::ItemPrice, 3, &[0x84])
+            .write(
+                RecordId::TextItemName,
+                3,
+                &[0x01, 0x47, 0x64, 0x5D, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+            )
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let weapon = Weapon::new(&rom, 3).unwrap();
+
+        assert_eq!(weapon.name, "{icon:1}Fib");
+        assert_eq!(weapon.price, 4000);
+        assert!(weapon.two_handed);
+        assert!(!weapon.long_range);
+        assert_eq!(weapon.attack, 30);
+        assert_eq!(
+            weapon.attributes,
+            Attributes {
+                element: 0x02,
+                race: 0x10,
+                status: 0x4000,
+            }
+        );
+        assert_eq!(weapon.equip_classes, 0x0081);
+        assert_eq!(weapon.stat_bonus.wisdom, 5);
+        assert_eq!(weapon.stat_bonus.will, 5);
+        assert_eq!(weapon.stat_bonus.strength, 0);
+    }
+
+    #[test]
+    fn test_armor_and_item_new() {
+        let rom = RomBuilder::new(Version::Us)
+            .write(
+                RecordId::ItemArmor,
+                0,
+                &[0x02, 0x05, 0x00, 0x01, 0x02, 0x00, 0x00, 0x00],
+            )
+            .write(RecordId::ItemConsumable, 1, &[0xC0, 0x30, 0x00, 0x00])
+            .write(RecordId::ItemPrice, FIRST_CONSUMABLE + 1, &[0x03])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let armor = Armor::new(&rom, 0).unwrap();
+        assert_eq!(armor.kind, ArmorKind::Body);
+        assert_eq!(armor.defense, 5);
+        assert_eq!(armor.magic_evasion, 2);
+
+        let item = Item::new(&rom, 1).unwrap();
+        assert!(item.usable_in_battle && item.usable_in_field && !item.throwable);
+        assert_eq!(item.spell, 0x30);
+        assert_eq!(item.price, 30);
+    }
+
+    #[test]
+    fn test_new_out_of_range() {
+        let rom = RomBuilder::new(Version::Us)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            Weapon::new(&rom, WEAPON_COUNT),
+            Err(Error::IndexOutOfRange {
+                record: "weapon",
+                index: WEAPON_COUNT,
+            })
+        ));
+        assert!(matches!(
+            Armor::new(&rom, ARMOR_COUNT),
+            Err(Error::IndexOutOfRange { .. })
+        ));
+        assert!(matches!(
+            Item::new(&rom, CONSUMABLE_COUNT),
+            Err(Error::IndexOutOfRange { .. })
+        ));
+    }
+}
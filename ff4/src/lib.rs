@@ -1,8 +1,20 @@
 pub mod character;
+pub mod event;
+pub mod formation;
 pub mod graphics;
+pub mod item;
 pub mod map;
 pub mod misc;
+pub mod monster;
+pub mod npc;
 pub mod rom;
+pub mod rom_map;
+pub mod shop;
+pub mod spell;
+pub mod text;
 pub mod title;
+pub mod trigger;
 
-mod rom_map;
+mod error;
+
+pub use error::Error;
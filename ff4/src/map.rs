@@ -1,11 +1,30 @@
+use std::fmt;
+
 use image::Rgba;
+use nom::{number::complete::le_u8, IResult};
 
+use crate::graphics;
 use crate::rom;
-use crate::rom_map::record;
+use crate::rom_map::record::RecordId;
+use crate::Error;
 
 pub const PIXELS_PER_TILE: usize = 64;
 pub const COMPOSED_TILES_PER_TILESET: usize = 128;
 pub const TILES_PER_TILESET: usize = 256;
+pub const COLORS_PER_SUB_PALETTE: usize = 16;
+
+const LOCATION_BATTLE_BACKGROUND: u8 = 0x0F;
+const LOCATION_BACKGROUND_ENABLED: u8 = 0x01;
+const LOCATION_BACKGROUND_TRANSLUCENT: u8 = 0x02;
+const LOCATION_BACKGROUND_SCROLL_HORIZONTAL: u8 = 0x04;
+const LOCATION_BACKGROUND_SCROLL_VERTICAL: u8 = 0x08;
+
+const INDOOR_TILE_NUMBER: u16 = 0x03FF;
+const INDOOR_TILE_PALETTE_SHIFT: u16 = 10;
+const INDOOR_TILE_PALETTE: u16 = 0x07;
+const INDOOR_TILE_PRIORITY: u16 = 0x2000;
+const INDOOR_TILE_HFLIP: u16 = 0x4000;
+const INDOOR_TILE_VFLIP: u16 = 0x8000;
 
 #[derive(Copy, Clone)]
 pub struct OutdoorTileProperties {
@@ -19,8 +38,8 @@ pub struct OutdoorTileProperties {
     big_whale: bool,
     hide_lower: bool,
     can_land_airship: bool,
-    encounters: bool,
-    trigger: bool,
+    pub encounters: bool,
+    pub trigger: bool,
     battle_background: usize,
 }
 
@@ -59,6 +78,60 @@ impl OutdoorTileProperties {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct IndoorTileProperties {
+    pub can_walk_lower: bool,
+    pub can_walk_upper: bool,
+    pub bridge: bool,
+    pub damage_floor: bool,
+    pub door: bool,
+    pub trigger: bool,
+}
+
+impl IndoorTileProperties {
+    pub fn new(data: &[u8]) -> Self {
+        Self {
+            can_walk_lower: data[0] & 0x01 > 0,
+            can_walk_upper: data[0] & 0x02 > 0,
+            bridge: data[0] & 0x04 > 0,
+            damage_floor: data[0] & 0x08 > 0,
+            door: data[0] & 0x10 > 0,
+            trigger: data[1] & 0x80 > 0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Facing {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Facing {
+    /// Decodes a facing from the low two bits of a value.
+    pub fn new(value: u8) -> Self {
+        match value & 0x03 {
+            0 => Facing::Up,
+            1 => Facing::Right,
+            2 => Facing::Down,
+            _ => Facing::Left,
+        }
+    }
+}
+
+impl fmt::Display for Facing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Facing::Up => write!(f, "up"),
+            Facing::Right => write!(f, "right"),
+            Facing::Down => write!(f, "down"),
+            Facing::Left => write!(f, "left"),
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq)]
 pub enum OutdoorMap {
     Overworld,
@@ -66,14 +139,37 @@ pub enum OutdoorMap {
     Moon,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BackgroundLayer {
+    pub tilemap: u8,
+    pub translucent: bool,
+    pub scroll_horizontal: bool,
+    pub scroll_vertical: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LocationHeader {
+    pub width: usize,
+    pub height: usize,
+    pub tileset: u8,
+    pub palette: u8,
+    pub music: u8,
+    pub tilemap: u8,
+    pub background: Option<BackgroundLayer>,
+    pub battle_background: u8,
+    pub encounter_rate: u8,
+}
+
 pub struct Map {
     pub height: usize,
     pub width: usize,
     pub tilemap: Vec<u8>,
+    pub background_tilemap: Option<Vec<u8>>,
+    pub header: Option<LocationHeader>,
 }
 
 impl Map {
-    pub fn new_outdoor(rom: &rom::Rom, map: OutdoorMap) -> Map {
+    pub fn new_outdoor(rom: &rom::Rom, map: OutdoorMap) -> Result<Map, Error> {
         let height = match map {
             OutdoorMap::Moon => 64,
             _ => 256,
@@ -81,19 +177,27 @@ impl Map {
 
         let width = height;
 
-        let tilemap_record = match map {
-            OutdoorMap::Overworld => record::OUTDOOR_TILEMAP_OVERWORLD,
-            OutdoorMap::Underworld => record::OUTDOOR_TILEMAP_UNDERWORLD,
-            OutdoorMap::Moon => record::OUTDOOR_TILEMAP_MOON,
+        let tilemap_id = match map {
+            OutdoorMap::Overworld => RecordId::OutdoorTilemapOverworld,
+            OutdoorMap::Underworld => RecordId::OutdoorTilemapUnderworld,
+            OutdoorMap::Moon => RecordId::OutdoorTilemapMoon,
         };
 
-        let encoded_tilemap = rom.read_bytes(tilemap_record, 0);
+        let tilemap_record = rom.record(tilemap_id)?;
+        let encoded_tilemap = rom.read_bytes(tilemap_id, 0)?;
         let mut tilemap = Vec::with_capacity(width * height);
 
         let mut index = 0;
 
+        let read_byte = |index: usize| {
+            encoded_tilemap.get(index).copied().ok_or(Error::Parse {
+                record: tilemap_record.name,
+                address: tilemap_record.address + index,
+            })
+        };
+
         while tilemap.len() < width * height {
-            match encoded_tilemap[index] {
+            match read_byte(index)? {
                 0x00 | 0x10 | 0x20 | 0x30 => {
                     tilemap.push(encoded_tilemap[index]);
 
@@ -110,7 +214,7 @@ impl Map {
                 x => {
                     index += 1;
 
-                    for _ in 0..(encoded_tilemap[index] as usize) + 1 {
+                    for _ in 0..(read_byte(index)? as usize) + 1 {
                         tilemap.push(x & 0x7F);
                     }
                 }
@@ -119,12 +223,111 @@ impl Map {
             index += 1;
         }
 
-        Map {
+        Ok(Map {
             height,
             width,
             tilemap,
+            background_tilemap: None,
+            header: None,
+        })
+    }
+
+    pub fn new_location(rom: &rom::Rom, id: usize) -> Result<Map, Error> {
+        let header = rom.parse_record(RecordId::LocationHeader, id, parse_location_header)?;
+        let size = header.width * header.height;
+
+        let tilemap = read_location_tilemap(rom, header.tilemap.into(), size)?;
+        let background_tilemap = match header.background {
+            Some(background) => Some(read_location_tilemap(rom, background.tilemap.into(), size)?),
+            None => None,
+        };
+
+        Ok(Map {
+            height: header.height,
+            width: header.width,
+            tilemap,
+            background_tilemap,
+            header: Some(header),
+        })
+    }
+}
+
+// Location tilemaps use the same run-length encoding as outdoor maps, without the overworld's
+// special cases.
+fn read_location_tilemap(rom: &rom::Rom, index: usize, size: usize) -> Result<Vec<u8>, Error> {
+    let pointer = rom.read_bytes(RecordId::LocationTilemapPointer, index)?;
+    let offset = usize::from(pointer[0]) | (usize::from(pointer[1]) << 8);
+
+    let record = rom.record(RecordId::LocationTilemapData)?;
+    let data = rom.read_bytes(RecordId::LocationTilemapData, 0)?;
+
+    let read_byte = |index: usize| {
+        data.get(offset + index).copied().ok_or(Error::Parse {
+            record: record.name,
+            address: record.address + offset + index,
+        })
+    };
+
+    let mut tilemap = Vec::with_capacity(size);
+    let mut index = 0;
+
+    while tilemap.len() < size {
+        match read_byte(index)? {
+            x if x < 0x80 => tilemap.push(x),
+            x => {
+                index += 1;
+
+                for _ in 0..(read_byte(index)? as usize) + 1 {
+                    tilemap.push(x & 0x7F);
+                }
+            }
         }
+
+        index += 1;
     }
+
+    tilemap.truncate(size);
+
+    Ok(tilemap)
+}
+
+pub fn parse_location_header(input: &[u8]) -> IResult<&[u8], LocationHeader> {
+    let (input, battle_background) = le_u8(input)?;
+    let (input, music) = le_u8(input)?;
+    let (input, tileset) = le_u8(input)?;
+    let (input, palette) = le_u8(input)?;
+    let (input, width) = le_u8(input)?;
+    let (input, height) = le_u8(input)?;
+    let (input, tilemap) = le_u8(input)?;
+    let (input, background_tilemap) = le_u8(input)?;
+    let (input, background_flags) = le_u8(input)?;
+    let (input, encounter_rate) = le_u8(input)?;
+
+    let background = if background_flags & LOCATION_BACKGROUND_ENABLED > 0 {
+        Some(BackgroundLayer {
+            tilemap: background_tilemap,
+            translucent: background_flags & LOCATION_BACKGROUND_TRANSLUCENT > 0,
+            scroll_horizontal: background_flags & LOCATION_BACKGROUND_SCROLL_HORIZONTAL > 0,
+            scroll_vertical: background_flags & LOCATION_BACKGROUND_SCROLL_VERTICAL > 0,
+        })
+    } else {
+        None
+    };
+
+    Ok((
+        input,
+        LocationHeader {
+            width: width.into(),
+            height: height.into(),
+            tileset,
+            palette,
+            music,
+            tilemap,
+            background,
+            battle_background: battle_background & LOCATION_BATTLE_BACKGROUND,
+            encounter_rate,
+        },
+    ))
 }
 
 pub struct TileComposition {
@@ -146,11 +349,11 @@ pub struct OutdoorTileset {
 }
 
 impl OutdoorTileset {
-    pub fn new(rom: &rom::Rom, map: OutdoorMap) -> OutdoorTileset {
+    pub fn new(rom: &rom::Rom, map: OutdoorMap) -> Result<OutdoorTileset, Error> {
         let map_index = map as usize;
 
-        let upper_values = rom.read_bytes(record::OUTDOOR_TILESET_UPPER_VALUES, map_index);
-        let lower_values = rom.read_bytes(record::OUTDOOR_TILESET_LOWER_VALUES, map_index);
+        let upper_values = rom.read_bytes(RecordId::OutdoorTilesetUpperValues, map_index)?;
+        let lower_values = rom.read_bytes(RecordId::OutdoorTilesetLowerValues, map_index)?;
 
         let tile_count = match map {
             OutdoorMap::Moon => 158,
@@ -177,7 +380,7 @@ impl OutdoorTileset {
             })
             .collect();
 
-        let composition_data = rom.read_bytes(record::OUTDOOR_TILESET_COMPOSITION, map_index);
+        let composition_data = rom.read_bytes(RecordId::OutdoorTilesetComposition, map_index)?;
 
         let composition = (0..COMPOSED_TILES_PER_TILESET)
             .map(|i| TileComposition {
@@ -188,17 +391,297 @@ impl OutdoorTileset {
             })
             .collect();
 
-        let properties_data = rom.read_bytes(record::OUTDOOR_TILE_PROPERTIES, map_index);
+        let properties_data = rom.read_bytes(RecordId::OutdoorTileProperties, map_index)?;
 
         let properties = (0..COMPOSED_TILES_PER_TILESET)
             .map(|i| OutdoorTileProperties::new(&properties_data[i * 2..(i + 1) * 2]))
             .collect();
 
-        OutdoorTileset {
+        Ok(OutdoorTileset {
             composition,
-            palette: rom.read_palette(record::OUTDOOR_TILESET_PALETTE, map_index, 1),
+            palette: rom.read_palette(RecordId::OutdoorTilesetPalette, map_index, 1)?,
             tiles,
             properties,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct IndoorTileReference {
+    pub tile: usize,
+    pub palette: usize,
+    pub priority: bool,
+    pub hflip: bool,
+    pub vflip: bool,
+}
+
+impl IndoorTileReference {
+    fn new(data: &[u8]) -> Self {
+        let value = u16::from(data[0]) | (u16::from(data[1]) << 8);
+
+        Self {
+            tile: usize::from(value & INDOOR_TILE_NUMBER),
+            palette: usize::from((value >> INDOOR_TILE_PALETTE_SHIFT) & INDOOR_TILE_PALETTE),
+            priority: value & INDOOR_TILE_PRIORITY > 0,
+            hflip: value & INDOOR_TILE_HFLIP > 0,
+            vflip: value & INDOOR_TILE_VFLIP > 0,
         }
     }
 }
+
+#[derive(Debug)]
+pub struct IndoorTileComposition {
+    pub upper_left: IndoorTileReference,
+    pub upper_right: IndoorTileReference,
+    pub lower_left: IndoorTileReference,
+    pub lower_right: IndoorTileReference,
+}
+
+impl IndoorTileComposition {
+    fn new(data: &[u8]) -> Self {
+        Self {
+            upper_left: IndoorTileReference::new(&data[0..2]),
+            upper_right: IndoorTileReference::new(&data[2..4]),
+            lower_left: IndoorTileReference::new(&data[4..6]),
+            lower_right: IndoorTileReference::new(&data[6..8]),
+        }
+    }
+}
+
+/// The graphics and properties of a location's tiles. Unlike the outdoor maps, each 8x8 tile is
+/// a standard 4bpp tile that picks one of eight sub-palettes and can be drawn above sprites.
+pub struct IndoorTileset {
+    pub composition: Vec<IndoorTileComposition>,
+    pub palette: Vec<Rgba<u8>>,
+    pub tiles: Vec<Vec<u8>>,
+    pub properties: Vec<IndoorTileProperties>,
+}
+
+impl IndoorTileset {
+    pub fn new(rom: &rom::Rom, tileset: usize, palette: usize) -> Result<IndoorTileset, Error> {
+        let graphics = rom.read_bytes(RecordId::IndoorTilesetGraphics, tileset)?;
+
+        let tiles = graphics
+            .chunks_exact(graphics::BYTES_PER_TILE_4BPP)
+            .map(graphics::parse_tile_4bpp)
+            .collect();
+
+        let composition = rom
+            .read_bytes(RecordId::IndoorTilesetComposition, tileset)?
+            .chunks_exact(8)
+            .map(IndoorTileComposition::new)
+            .collect();
+
+        let properties = rom
+            .read_bytes(RecordId::IndoorTileProperties, tileset)?
+            .chunks_exact(2)
+            .map(IndoorTileProperties::new)
+            .collect();
+
+        Ok(IndoorTileset {
+            composition,
+            palette: rom.read_palette(RecordId::IndoorTilesetPalette, palette, 1)?,
+            tiles,
+            properties,
+        })
+    }
+
+    pub fn sub_palette(&self, index: usize) -> Option<&[Rgba<u8>]> {
+        let start = index * COLORS_PER_SUB_PALETTE;
+        self.palette.get(start..start + COLORS_PER_SUB_PALETTE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_map_new_outdoor() {
+        let rom = RomBuilder::new(Version::Us)
+            .write(
+                RecordId::OutdoorTilemapOverworld,
+                0,
+                &[0x05, 0x83, 0x02, 0x10, 0xFF, 0x06, 0x80, 0x22],
+            )
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let map = Map::new_outdoor(&rom, OutdoorMap::Overworld).unwrap();
+
+        assert_eq!(map.width, 256);
+        assert_eq!(map.height, 256);
+        assert_eq!(map.tilemap.len(), 256 * 256);
+        assert_eq!(
+            map.tilemap[0..9],
+            [0x05, 0x03, 0x03, 0x03, 0x10, 0x73, 0x74, 0x75, 0x06]
+        );
+        assert_eq!(map.tilemap[9..44], [0x00; 35]);
+        assert_eq!(map.tilemap[44..48], [0x00, 0x70, 0x71, 0x72]);
+
+        assert!(matches!(
+            Map::new_outdoor(&rom, OutdoorMap::Moon),
+            Err(Error::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_map_new_location() {
+        let mut tilemaps = vec![0; 0x20];
+        tilemaps.extend_from_slice(&[0x01, 0x82, 0x0D, 0x03]);
+        tilemaps.extend_from_slice(&[0x85, 0x0F]);
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(
+                RecordId::LocationHeader,
+                0x12,
+                &[0x23, 0x05, 0x02, 0x04, 0x04, 0x04, 0x01, 0x02, 0x03, 0x08],
+            )
+            .write(RecordId::LocationTilemapPointer, 1, &[0x20, 0x00])
+            .write(RecordId::LocationTilemapPointer, 2, &[0x24, 0x00])
+            .write(RecordId::LocationTilemapData, 0, &tilemaps)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let map = Map::new_location(&rom, 0x12).unwrap();
+
+        assert_eq!((map.width, map.height), (4, 4));
+        assert_eq!(map.tilemap[0..4], [0x01, 0x02, 0x02, 0x02]);
+        assert_eq!(map.tilemap[14..16], [0x02, 0x03]);
+        assert_eq!(map.background_tilemap, Some(vec![0x05; 16]));
+
+        let header = map.header.unwrap();
+        assert_eq!(header.battle_background, 3);
+        assert_eq!(header.music, 5);
+        assert_eq!(header.tileset, 2);
+        assert_eq!(header.palette, 4);
+        assert_eq!(header.encounter_rate, 8);
+        assert_eq!(
+            header.background,
+            Some(BackgroundLayer {
+                tilemap: 2,
+                translucent: true,
+                scroll_horizontal: false,
+                scroll_vertical: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_outdoor_tileset_new() {
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::OutdoorTilesetUpperValues, 1, &[0x10, 0x20])
+            .write(RecordId::OutdoorTilesetLowerValues, 1, &[0x21])
+            .write(RecordId::OutdoorTilesetComposition, 1, &{
+                let mut composition = [0; COMPOSED_TILES_PER_TILESET * 4];
+                for i in 0..4 {
+                    composition[COMPOSED_TILES_PER_TILESET * i] = 4 + i as u8;
+                }
+                composition
+            })
+            .write(RecordId::OutdoorTileProperties, 1, &[0x09, 0xC3])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let tileset = OutdoorTileset::new(&rom, OutdoorMap::Underworld).unwrap();
+
+        assert_eq!(tileset.tiles.len(), TILES_PER_TILESET);
+        assert_eq!(tileset.tiles[0].pixels[0..3], [0x11, 0x12, 0x10]);
+        assert_eq!(tileset.tiles[1].pixels[0], 0x20);
+
+        assert_eq!(tileset.composition[0].upper_left, 4);
+        assert_eq!(tileset.composition[0].upper_right, 5);
+        assert_eq!(tileset.composition[0].lower_left, 6);
+        assert_eq!(tileset.composition[0].lower_right, 7);
+
+        assert!(tileset.properties[0].can_walk_low);
+        assert!(tileset.properties[0].forest);
+        assert!(!tileset.properties[1].can_walk_low);
+
+        assert_eq!(tileset.palette.len(), 64);
+    }
+
+    #[test]
+    fn test_indoor_tileset_new() {
+        let mut graphics = [0; graphics::BYTES_PER_TILE_4BPP * 2];
+        graphics[graphics::BYTES_PER_TILE_4BPP] = 0x80;
+        graphics[graphics::BYTES_PER_TILE_4BPP + 17] = 0x80;
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::IndoorTilesetGraphics, 2, &graphics)
+            .write(RecordId::IndoorTilesetGraphics, 6, &graphics)
+            .write(
+                RecordId::IndoorTilesetComposition,
+                2,
+                &[0x01, 0x00, 0x01, 0x64, 0x02, 0x98, 0xFF, 0x23],
+            )
+            .write(RecordId::IndoorTileProperties, 2, &[0x05, 0x00, 0x1A, 0x80])
+            .write(RecordId::IndoorTilesetPalette, 1, &[0x1F, 0x00])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let tileset = IndoorTileset::new(&rom, 2, 1).unwrap();
+
+        assert_eq!(tileset.tiles.len(), TILES_PER_TILESET);
+        assert_eq!(tileset.tiles[0], vec![0; graphics::BYTES_PER_TILE_UNPACKED]);
+        assert_eq!(tileset.tiles[1][0..2], [0x09, 0x00]);
+        assert_eq!(
+            IndoorTileset::new(&rom, 6, 1).unwrap().tiles[1][0..2],
+            [0x09, 0x00]
+        );
+
+        assert_eq!(
+            tileset.composition[0].upper_left,
+            IndoorTileReference {
+                tile: 1,
+                palette: 0,
+                priority: false,
+                hflip: false,
+                vflip: false,
+            }
+        );
+        assert_eq!(
+            tileset.composition[0].upper_right,
+            IndoorTileReference {
+                tile: 1,
+                palette: 1,
+                priority: true,
+                hflip: true,
+                vflip: false,
+            }
+        );
+        assert_eq!(tileset.composition[0].lower_left.palette, 6);
+        assert!(tileset.composition[0].lower_left.vflip);
+        assert_eq!(tileset.composition[0].lower_right.tile, 0x3FF);
+
+        assert_eq!(
+            tileset.properties[0],
+            IndoorTileProperties {
+                can_walk_lower: true,
+                can_walk_upper: false,
+                bridge: true,
+                damage_floor: false,
+                door: false,
+                trigger: false,
+            }
+        );
+        assert!(tileset.properties[1].can_walk_upper);
+        assert!(tileset.properties[1].damage_floor);
+        assert!(tileset.properties[1].door);
+        assert!(tileset.properties[1].trigger);
+
+        assert_eq!(tileset.palette.len(), 128);
+        assert_eq!(tileset.sub_palette(0).unwrap()[0], Rgba([255, 0, 0, 255]));
+        assert_eq!(
+            tileset.sub_palette(7).unwrap().len(),
+            COLORS_PER_SUB_PALETTE
+        );
+        assert!(tileset.sub_palette(8).is_none());
+    }
+}
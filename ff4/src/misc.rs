@@ -1,16 +1,20 @@
 use crate::rom;
-use crate::rom_map;
+use crate::rom_map::record::RecordId;
+use crate::Error;
 
-pub fn get_ocean_animation_line(rom: &rom::Rom, frame: usize) -> (usize, usize) {
-    let record = rom_map::record::OCEAN_ANIMATION_SEQUENCE;
-    let line = usize::from(rom.read_u8(record.address + frame % 16)) >> 3;
+pub fn get_ocean_animation_line(rom: &rom::Rom, frame: usize) -> Result<(usize, usize), Error> {
+    let record = rom.record(RecordId::OceanAnimationSequence)?;
+    let line = usize::from(rom.read_u8(record.address + frame % 16)?) >> 3;
 
-    (line / 16 * 2, line % 16)
+    Ok((line / 16 * 2, line % 16))
 }
 
-pub fn get_waterfall_animation_column(rom: &rom::Rom, frame: usize) -> (usize, usize) {
-    let record = rom_map::record::WATERFALL_ANIMATION_SEQUENCE;
-    let byte = usize::from(rom.read_u8(record.address + frame % 16));
+pub fn get_waterfall_animation_column(
+    rom: &rom::Rom,
+    frame: usize,
+) -> Result<(usize, usize), Error> {
+    let record = rom.record(RecordId::WaterfallAnimationSequence)?;
+    let byte = usize::from(rom.read_u8(record.address + frame % 16)?);
 
-    (byte / 0x40, byte % 0x40)
+    Ok((byte / 0x40, byte % 0x40))
 }
@@ -0,0 +1,488 @@
+use std::fmt;
+
+use nom::{
+    number::complete::{le_u16, le_u8},
+    IResult,
+};
+
+use crate::rom;
+use crate::rom_map::record::RecordId;
+use crate::Error;
+
+const LAST_SPELL: u8 = 0xBF;
+const LAST_COMMAND: u8 = 0xE7;
+
+const ACTION_TARGET: u8 = 0xE8;
+const ACTION_MESSAGE: u8 = 0xE9;
+const ACTION_TRANSFORM: u8 = 0xEA;
+const ACTION_MUSIC: u8 = 0xEB;
+const ACTION_SET_VARIABLE: u8 = 0xEC;
+const ACTION_CHAIN_START: u8 = 0xED;
+const ACTION_CHAIN_END: u8 = 0xEE;
+const ACTION_WAIT: u8 = 0xEF;
+const ACTION_END_TURN: u8 = 0xFD;
+const ACTIONS_END: u8 = 0xFE;
+const SCRIPT_END: u8 = 0xFF;
+
+const NO_CONDITION: u8 = 0xFF;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScriptKind {
+    Normal,
+    Reaction,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Target {
+    Myself,
+    AllMonsters,
+    RandomMonster,
+    AllCharacters,
+    RandomCharacter,
+    FrontRow,
+    BackRow,
+    Character(u8),
+    Monster(u8),
+    Other(u8),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Condition {
+    Always,
+    Variable { variable: u8, value: u8 },
+    HpBelow { target: Target, hp: u16 },
+    Alone,
+    Status { target: Target, status: u16 },
+    MonsterAlive(u8),
+    HitByCommand(u8),
+    HitBySpell(u8),
+    HitByElement(u8),
+    Damaged,
+    Other { kind: u8, parameters: [u8; 3] },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Action {
+    Spell(u8),
+    Command(u8),
+    Target(Target),
+    Message(u8),
+    Transform(u8),
+    Music(u8),
+    SetVariable { variable: u8, value: u8 },
+    ChainStart,
+    ChainEnd,
+    Wait,
+    EndTurn,
+    Unknown(u8),
+}
+
+/// A list of actions performed when every one of its conditions holds. The first entry whose
+/// conditions hold is the one used.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub conditions: Vec<Condition>,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Script {
+    pub kind: ScriptKind,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct EntryRecord {
+    condition_set: u8,
+    actions: Vec<Action>,
+}
+
+impl Script {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        Script::read(
+            rom,
+            RecordId::MonsterScriptPointer,
+            index,
+            ScriptKind::Normal,
+        )
+    }
+
+    pub fn new_reaction(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        Script::read(
+            rom,
+            RecordId::MonsterReactionPointer,
+            index,
+            ScriptKind::Reaction,
+        )
+    }
+
+    fn read(
+        rom: &rom::Rom,
+        pointer: RecordId,
+        index: usize,
+        kind: ScriptKind,
+    ) -> Result<Self, Error> {
+        let record = rom.record(RecordId::MonsterScriptData)?;
+        let offset = usize::from(rom.parse_record(pointer, index, parse_pointer)?);
+        let data = rom.read_bytes(RecordId::MonsterScriptData, 0)?;
+
+        let records = data
+            .get(offset..)
+            .and_then(|input| parse_script(input).ok())
+            .map(|(_, records)| records)
+            .ok_or(Error::Parse {
+                record: record.name,
+                address: record.address + offset,
+            })?;
+
+        let entries = records
+            .into_iter()
+            .map(|entry| {
+                Ok(Entry {
+                    conditions: get_condition_set(rom, entry.condition_set.into())?,
+                    actions: entry.actions,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Script { kind, entries })
+    }
+}
+
+pub fn get_condition_set(rom: &rom::Rom, index: usize) -> Result<Vec<Condition>, Error> {
+    rom.read_bytes(RecordId::MonsterConditionSet, index)?
+        .iter()
+        .filter(|&&condition| condition != NO_CONDITION)
+        .map(|&condition| {
+            rom.parse_record(
+                RecordId::MonsterCondition,
+                condition.into(),
+                parse_condition,
+            )
+        })
+        .collect()
+}
+
+pub(crate) fn parse_script(input: &[u8]) -> IResult<&[u8], Vec<EntryRecord>> {
+    let mut input = input;
+    let mut entries = Vec::new();
+
+    loop {
+        let (rest, condition_set) = le_u8(input)?;
+
+        if condition_set == SCRIPT_END {
+            return Ok((rest, entries));
+        }
+
+        let (rest, actions) = parse_actions(rest)?;
+        entries.push(EntryRecord {
+            condition_set,
+            actions,
+        });
+        input = rest;
+    }
+}
+
+pub fn parse_actions(input: &[u8]) -> IResult<&[u8], Vec<Action>> {
+    let mut input = input;
+    let mut actions = Vec::new();
+
+    loop {
+        let (rest, code) = le_u8(input)?;
+
+        let (rest, action) = match code {
+            ACTIONS_END => return Ok((rest, actions)),
+            0..=LAST_SPELL => (rest, Action::Spell(code)),
+            0xC0..=LAST_COMMAND => (rest, Action::Command(code - 0xC0)),
+            ACTION_TARGET => {
+                let (rest, target) = le_u8(rest)?;
+                (rest, Action::Target(decode_target(target)))
+            }
+            ACTION_MESSAGE => {
+                let (rest, message) = le_u8(rest)?;
+                (rest, Action::Message(message))
+            }
+            ACTION_TRANSFORM => {
+                let (rest, monster) = le_u8(rest)?;
+                (rest, Action::Transform(monster))
+            }
+            ACTION_MUSIC => {
+                let (rest, song) = le_u8(rest)?;
+                (rest, Action::Music(song))
+            }
+            ACTION_SET_VARIABLE => {
+                let (rest, variable) = le_u8(rest)?;
+                let (rest, value) = le_u8(rest)?;
+                (rest, Action::SetVariable { variable, value })
+            }
+            ACTION_CHAIN_START => (rest, Action::ChainStart),
+            ACTION_CHAIN_END => (rest, Action::ChainEnd),
+            ACTION_WAIT => (rest, Action::Wait),
+            ACTION_END_TURN => (rest, Action::EndTurn),
+            _ => (rest, Action::Unknown(code)),
+        };
+
+        actions.push(action);
+        input = rest;
+    }
+}
+
+pub fn parse_condition(input: &[u8]) -> IResult<&[u8], Condition> {
+    let (input, kind) = le_u8(input)?;
+    let (input, a) = le_u8(input)?;
+    let (input, b) = le_u8(input)?;
+    let (input, c) = le_u8(input)?;
+    let value = u16::from(b) | (u16::from(c) << 8);
+
+    let condition = match kind {
+        0x00 => Condition::Always,
+        0x01 => Condition::Variable {
+            variable: a,
+            value: b,
+        },
+        0x02 => Condition::HpBelow {
+            target: decode_target(a),
+            hp: value,
+        },
+        0x03 => Condition::Alone,
+        0x04 => Condition::Status {
+            target: decode_target(a),
+            status: value,
+        },
+        0x05 => Condition::MonsterAlive(a),
+        0x06 => Condition::HitByCommand(a),
+        0x07 => Condition::HitBySpell(a),
+        0x08 => Condition::HitByElement(a),
+        0x09 => Condition::Damaged,
+        _ => Condition::Other {
+            kind,
+            parameters: [a, b, c],
+        },
+    };
+
+    Ok((input, condition))
+}
+
+pub fn decode_target(value: u8) -> Target {
+    match value {
+        0x00 => Target::Myself,
+        0x01 => Target::AllMonsters,
+        0x02 => Target::RandomMonster,
+        0x03 => Target::AllCharacters,
+        0x04 => Target::RandomCharacter,
+        0x05 => Target::FrontRow,
+        0x06 => Target::BackRow,
+        0x08..=0x0C => Target::Character(value - 0x08),
+        0x10..=0x17 => Target::Monster(value - 0x10),
+        _ => Target::Other(value),
+    }
+}
+
+fn parse_pointer(input: &[u8]) -> IResult<&[u8], u16> {
+    le_u16(input)
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::Myself => write!(f, "self"),
+            Target::AllMonsters => write!(f, "all monsters"),
+            Target::RandomMonster => write!(f, "random monster"),
+            Target::AllCharacters => write!(f, "all characters"),
+            Target::RandomCharacter => write!(f, "random character"),
+            Target::FrontRow => write!(f, "front row"),
+            Target::BackRow => write!(f, "back row"),
+            Target::Character(slot) => write!(f, "character {}", slot),
+            Target::Monster(slot) => write!(f, "monster {}", slot),
+            Target::Other(value) => write!(f, "target ${:02X}", value),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Always => write!(f, "always"),
+            Condition::Variable { variable, value } => {
+                write!(f, "variable {} is {}", variable, value)
+            }
+            Condition::HpBelow { target, hp } => write!(f, "HP of {} below {}", target, hp),
+            Condition::Alone => write!(f, "alone"),
+            Condition::Status { target, status } => {
+                write!(f, "{} has status ${:04X}", target, status)
+            }
+            Condition::MonsterAlive(monster) => write!(f, "monster ${:02X} alive", monster),
+            Condition::HitByCommand(command) => write!(f, "hit by command ${:02X}", command),
+            Condition::HitBySpell(spell) => write!(f, "hit by spell ${:02X}", spell),
+            Condition::HitByElement(element) => write!(f, "hit by element ${:02X}", element),
+            Condition::Damaged => write!(f, "damaged"),
+            Condition::Other { kind, parameters } => write!(
+                f,
+                "condition ${:02X} ${:02X} ${:02X} ${:02X}",
+                kind, parameters[0], parameters[1], parameters[2]
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::Spell(spell) => write!(f, "cast spell ${:02X}", spell),
+            Action::Command(command) => write!(f, "use command ${:02X}", command),
+            Action::Target(target) => write!(f, "target {}", target),
+            Action::Message(message) => write!(f, "show message ${:02X}", message),
+            Action::Transform(monster) => write!(f, "transform into monster ${:02X}", monster),
+            Action::Music(song) => write!(f, "play song ${:02X}", song),
+            Action::SetVariable { variable, value } => {
+                write!(f, "set variable {} to {}", variable, value)
+            }
+            Action::ChainStart => write!(f, "begin chain"),
+            Action::ChainEnd => write!(f, "end chain"),
+            Action::Wait => write!(f, "wait"),
+            Action::EndTurn => write!(f, "end turn"),
+            Action::Unknown(code) => write!(f, "unknown ${:02X}", code),
+        }
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keyword = match self.kind {
+            ScriptKind::Normal => "if",
+            ScriptKind::Reaction => "when",
+        };
+
+        for entry in &self.entries {
+            let conditions: Vec<String> = entry.conditions.iter().map(|c| c.to_string()).collect();
+            let conditions = if conditions.is_empty() {
+                Condition::Always.to_string()
+            } else {
+                conditions.join(" and ")
+            };
+
+            writeln!(f, "{} {}:", keyword, conditions)?;
+
+            for action in &entry.actions {
+                writeln!(f, "    {}", action)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_parse_script() {
+        assert_eq!(
+            parse_script(&[0x01, 0xE8, 0x04, 0x30, 0xC0, 0xFE, 0x00, 0xE9, 0x02, 0xFD, 0xFE, 0xFF]),
+            Ok((
+                &[][..],
+                vec![
+                    EntryRecord {
+                        condition_set: 1,
+                        actions: vec![
+                            Action::Target(Target::RandomCharacter),
+                            Action::Spell(0x30),
+                            Action::Command(0),
+                        ],
+                    },
+                    EntryRecord {
+                        condition_set: 0,
+                        actions: vec![Action::Message(2), Action::EndTurn],
+                    },
+                ]
+            ))
+        );
+
+        assert!(parse_script(&[0x01, 0x30]).is_err());
+    }
+
+    #[test]
+    fn test_parse_condition() {
+        assert_eq!(
+            parse_condition(&[0x02, 0x00, 0xE8, 0x03]),
+            Ok((
+                &[][..],
+                Condition::HpBelow {
+                    target: Target::Myself,
+                    hp: 1000,
+                }
+            ))
+        );
+        assert_eq!(
+            parse_condition(&[0x01, 0x02, 0x05, 0x00]),
+            Ok((
+                &[][..],
+                Condition::Variable {
+                    variable: 2,
+                    value: 5,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_script_new() {
+        let mut data = vec![0; 0x20];
+        data.extend_from_slice(&[0x01, 0xEC, 0x00, 0x01, 0x10, 0xFE, 0xFF]);
+        data.extend_from_slice(&[0x02, 0xE8, 0x00, 0xC1, 0xFE, 0xFF]);
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::MonsterScriptPointer, 4, &[0x20, 0x00])
+            .write(RecordId::MonsterReactionPointer, 1, &[0x27, 0x00])
+            .write(RecordId::MonsterScriptData, 0, &data)
+            .write(RecordId::MonsterConditionSet, 1, &[0x03, 0x04, 0xFF, 0xFF])
+            .write(RecordId::MonsterConditionSet, 2, &[0x05, 0xFF, 0xFF, 0xFF])
+            .write(RecordId::MonsterCondition, 3, &[0x03, 0x00, 0x00, 0x00])
+            .write(RecordId::MonsterCondition, 4, &[0x01, 0x00, 0x00, 0x00])
+            .write(RecordId::MonsterCondition, 5, &[0x07, 0x31, 0x00, 0x00])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let script = Script::new(&rom, 4).unwrap();
+        assert_eq!(
+            script.to_string(),
+            "if alone and variable 0 is 0:\n    set variable 0 to 1\n    cast spell $10\n"
+        );
+
+        let reaction = Script::new_reaction(&rom, 1).unwrap();
+        assert_eq!(reaction.kind, ScriptKind::Reaction);
+        assert_eq!(
+            reaction.to_string(),
+            "when hit by spell $31:\n    target self\n    use command $01\n"
+        );
+    }
+
+    #[test]
+    fn test_script_new_invalid() {
+        let length = 0x2000;
+        let mut data = vec![0; length];
+        // An entry cut off by the end of the script data, with no terminator.
+        data[length - 2..].copy_from_slice(&[0x01, 0x10]);
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::MonsterScriptPointer, 0, &[0xFE, 0x1F])
+            .write(RecordId::MonsterScriptPointer, 1, &[0x00, 0x30])
+            .write(RecordId::MonsterScriptData, 0, &data)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            Script::new(&rom, 0),
+            Err(Error::Parse {
+                address: 0x0ED2FE,
+                ..
+            })
+        ));
+        assert!(matches!(Script::new(&rom, 1), Err(Error::Parse { .. })));
+    }
+}
@@ -0,0 +1,334 @@
+use nom::{
+    number::complete::{le_u16, le_u8},
+    IResult,
+};
+
+use crate::rom;
+use crate::rom_map::record::RecordId;
+use crate::text;
+use crate::Error;
+
+pub mod ai;
+
+const LEVEL: u8 = 0x7F;
+const BOSS: u8 = 0x80;
+
+const DROP_TABLE: u8 = 0x3F;
+const DROP_RATE_SHIFT: u8 = 6;
+const DROP_RATES: [u8; 4] = [0, 5, 25, 100];
+
+const EXTRA_ATTACK_ATTRIBUTES: u8 = 0x80;
+const EXTRA_RESISTANCES: u8 = 0x40;
+const EXTRA_WEAKNESSES: u8 = 0x20;
+const EXTRA_RACE: u8 = 0x10;
+const EXTRA_REACTION_SCRIPT: u8 = 0x08;
+
+/// One of attack, defense or magic defense: the number of hits or blocks, the chance of each
+/// and the base value.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct StatMultiplier {
+    pub multiplier: u8,
+    pub percent: u8,
+    pub value: u8,
+}
+
+/// The items a monster can drop, from most to least common, and the chance of any drop.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Drops {
+    pub rate: u8,
+    pub items: [u8; 4],
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Monster {
+    pub name: String,
+    pub boss: bool,
+    pub level: u8,
+    pub hp: u16,
+    pub attack: StatMultiplier,
+    pub defense: StatMultiplier,
+    pub magic_defense: StatMultiplier,
+    pub speed_minimum: u8,
+    pub speed_maximum: u8,
+    pub attack_element: u8,
+    pub attack_status: u16,
+    pub resistances: u8,
+    pub immunities: u16,
+    pub weaknesses: u8,
+    pub race: u8,
+    pub gil: u16,
+    pub experience: u16,
+    pub drops: Drops,
+    pub script: u8,
+    pub reaction_script: Option<u8>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct MonsterRecord {
+    boss: bool,
+    level: u8,
+    hp: u16,
+    attack: u8,
+    defense: u8,
+    magic_defense: u8,
+    speed: u8,
+    drop_table: u8,
+    drop_rate: u8,
+    script: u8,
+    attack_element: u8,
+    attack_status: u16,
+    resistances: u8,
+    immunities: u16,
+    weaknesses: u8,
+    race: u8,
+    reaction_script: Option<u8>,
+}
+
+impl Monster {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let record = rom.record(RecordId::MonsterData)?;
+        let offset = usize::from(rom.parse_record(RecordId::MonsterPointer, index, parse_u16)?);
+        let data = rom.read_bytes(RecordId::MonsterData, 0)?;
+
+        let monster = data
+            .get(offset..)
+            .and_then(|input| parse_monster(input).ok())
+            .map(|(_, monster)| monster)
+            .ok_or(Error::Parse {
+                record: record.name,
+                address: record.address + offset,
+            })?;
+
+        let speed = rom.read_bytes(RecordId::MonsterSpeed, monster.speed.into())?;
+        let drops = rom.read_bytes(RecordId::MonsterDrops, monster.drop_table.into())?;
+
+        Ok(Monster {
+            name: text::read_name(rom, RecordId::TextMonsterName, index)?,
+            boss: monster.boss,
+            level: monster.level,
+            hp: monster.hp,
+            attack: get_stat_multiplier(rom, monster.attack.into())?,
+            defense: get_stat_multiplier(rom, monster.defense.into())?,
+            magic_defense: get_stat_multiplier(rom, monster.magic_defense.into())?,
+            speed_minimum: speed[0],
+            speed_maximum: speed[1],
+            attack_element: monster.attack_element,
+            attack_status: monster.attack_status,
+            resistances: monster.resistances,
+            immunities: monster.immunities,
+            weaknesses: monster.weaknesses,
+            race: monster.race,
+            gil: rom.parse_record(RecordId::MonsterGil, index, parse_u16)?,
+            experience: rom.parse_record(RecordId::MonsterExperience, index, parse_u16)?,
+            drops: Drops {
+                rate: DROP_RATES[usize::from(monster.drop_rate)],
+                items: [drops[0], drops[1], drops[2], drops[3]],
+            },
+            script: monster.script,
+            reaction_script: monster.reaction_script,
+        })
+    }
+}
+
+pub fn get_stat_multiplier(rom: &rom::Rom, index: usize) -> Result<StatMultiplier, Error> {
+    rom.parse_record(RecordId::MonsterStats, index, parse_stat_multiplier)
+}
+
+pub(crate) fn parse_monster(input: &[u8]) -> IResult<&[u8], MonsterRecord> {
+    let (input, level_boss) = le_u8(input)?;
+    let (input, hp) = le_u16(input)?;
+    let (input, attack) = le_u8(input)?;
+    let (input, defense) = le_u8(input)?;
+    let (input, magic_defense) = le_u8(input)?;
+    let (input, speed) = le_u8(input)?;
+    let (input, drops) = le_u8(input)?;
+    let (input, script) = le_u8(input)?;
+    let (mut input, extra) = le_u8(input)?;
+
+    let mut monster = MonsterRecord {
+        boss: level_boss & BOSS > 0,
+        level: level_boss & LEVEL,
+        hp,
+        attack,
+        defense,
+        magic_defense,
+        speed,
+        drop_table: drops & DROP_TABLE,
+        drop_rate: drops >> DROP_RATE_SHIFT,
+        script,
+        ..MonsterRecord::default()
+    };
+
+    if extra & EXTRA_ATTACK_ATTRIBUTES > 0 {
+        let (rest, element) = le_u8(input)?;
+        let (rest, status) = le_u16(rest)?;
+        monster.attack_element = element;
+        monster.attack_status = status;
+        input = rest;
+    }
+
+    if extra & EXTRA_RESISTANCES > 0 {
+        let (rest, element) = le_u8(input)?;
+        let (rest, status) = le_u16(rest)?;
+        monster.resistances = element;
+        monster.immunities = status;
+        input = rest;
+    }
+
+    if extra & EXTRA_WEAKNESSES > 0 {
+        let (rest, weaknesses) = le_u8(input)?;
+        monster.weaknesses = weaknesses;
+        input = rest;
+    }
+
+    if extra & EXTRA_RACE > 0 {
+        let (rest, race) = le_u8(input)?;
+        monster.race = race;
+        input = rest;
+    }
+
+    if extra & EXTRA_REACTION_SCRIPT > 0 {
+        let (rest, reaction_script) = le_u8(input)?;
+        monster.reaction_script = Some(reaction_script);
+        input = rest;
+    }
+
+    Ok((input, monster))
+}
+
+pub fn parse_stat_multiplier(input: &[u8]) -> IResult<&[u8], StatMultiplier> {
+    let (input, multiplier) = le_u8(input)?;
+    let (input, percent) = le_u8(input)?;
+    let (input, value) = le_u8(input)?;
+
+    Ok((
+        input,
+        StatMultiplier {
+            multiplier,
+            percent,
+            value,
+        },
+    ))
+}
+
+fn parse_u16(input: &[u8]) -> IResult<&[u8], u16> {
+    le_u16(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_parse_monster() {
+        assert_eq!(
+            parse_monster(&[
+                0x85, 0xE8, 0x03, 0x01, 0x02, 0x03, 0x04, 0x45, 0x06, 0x38, 0x02, 0x40, 0x00, 0x10,
+                0x07
+            ]),
+            Ok((
+                &[0x10, 0x07][..],
+                MonsterRecord {
+                    boss: true,
+                    level: 5,
+                    hp: 1000,
+                    attack: 1,
+                    defense: 2,
+                    magic_defense: 3,
+                    speed: 4,
+                    drop_table: 5,
+                    drop_rate: 1,
+                    script: 6,
+                    weaknesses: 0x02,
+                    race: 0x40,
+                    reaction_script: Some(0x00),
+                    ..MonsterRecord::default()
+                }
+            ))
+        );
+
+        // The flags announce attack attributes that the data does not contain.
+        assert!(
+            parse_monster(&[0x05, 0xE8, 0x03, 0x01, 0x02, 0x03, 0x04, 0x45, 0x06, 0x80]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_monster_new() {
+        let mut data = vec![0; 0x10];
+        data.extend_from_slice(&[
+            0x03, 0x64, 0x00, 0x01, 0x00, 0x00, 0x02, 0xC3, 0x00, 0x80, 0x01, 0x00, 0x10,
+        ]);
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::MonsterPointer, 1, &[0x10, 0x00])
+            .write(RecordId::MonsterData, 0, &data)
+            .write(RecordId::MonsterStats, 1, &[0x02, 0x50, 0x0A])
+            .write(RecordId::MonsterSpeed, 2, &[0x08, 0x0C])
+            .write(RecordId::MonsterDrops, 3, &[0xB0, 0xB1, 0xB2, 0xB3])
+            .write(RecordId::MonsterGil, 1, &[0x20, 0x00])
+            .write(RecordId::MonsterExperience, 1, &[0x34, 0x12])
+            .write(
+                RecordId::TextMonsterName,
+                1,
+                &[0x48, 0x67, 0x6A, 0x5C, 0x68, 0xFF, 0xFF, 0xFF],
+            )
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let monster = Monster::new(&rom, 1).unwrap();
+
+        assert_eq!(monster.name, "Gloam");
+        assert_eq!(monster.level, 3);
+        assert_eq!(monster.hp, 100);
+        assert_eq!(
+            monster.attack,
+            StatMultiplier {
+                multiplier: 2,
+                percent: 80,
+                value: 10,
+            }
+        );
+        assert_eq!(monster.defense, StatMultiplier::default());
+        assert_eq!((monster.speed_minimum, monster.speed_maximum), (8, 12));
+        assert_eq!(monster.attack_element, 0x01);
+        assert_eq!(monster.attack_status, 0x1000);
+        assert_eq!(monster.gil, 32);
+        assert_eq!(monster.experience, 0x1234);
+        assert_eq!(
+            monster.drops,
+            Drops {
+                rate: 100,
+                items: [0xB0, 0xB1, 0xB2, 0xB3],
+            }
+        );
+        assert_eq!(monster.reaction_script, None);
+    }
+
+    #[test]
+    fn test_monster_new_invalid() {
+        let mut data = vec![0; 0x1E00];
+        // A monster cut off by the end of the monster data.
+        data[0x1DFC..].copy_from_slice(&[0x03, 0x64, 0x00, 0x01]);
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::MonsterPointer, 0, &[0xFC, 0x1D])
+            .write(RecordId::MonsterPointer, 1, &[0x00, 0x20])
+            .write(RecordId::MonsterData, 0, &data)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            Monster::new(&rom, 0),
+            Err(Error::Parse {
+                address: 0x0E9FBC,
+                ..
+            })
+        ));
+        assert!(matches!(Monster::new(&rom, 1), Err(Error::Parse { .. })));
+    }
+}
@@ -0,0 +1,160 @@
+use nom::{
+    multi::count,
+    number::complete::{le_u16, le_u8},
+    IResult,
+};
+
+use crate::map::Facing;
+use crate::rom;
+use crate::rom_map::record::RecordId;
+use crate::Error;
+
+const FLAGS_MOVEMENT_SHIFT: u8 = 2;
+const FLAGS_MOVEMENT: u8 = 0x07;
+const FLAGS_INTANGIBLE: u8 = 0x80;
+
+const ALWAYS_VISIBLE: u8 = 0xFF;
+const NO_EVENT: u8 = 0xFF;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Movement {
+    Still,
+    Wander,
+    PaceHorizontal,
+    PaceVertical,
+    Spin,
+    Unknown(u8),
+}
+
+/// An NPC standing on a location. The sprite indexes the NPC field sprite sheets, and the NPC is
+/// only shown while its visibility flag, if it has one, is set.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NpcPlacement {
+    pub sprite: u8,
+    pub x: u8,
+    pub y: u8,
+    pub facing: Facing,
+    pub movement: Movement,
+    pub intangible: bool,
+    pub visibility_flag: Option<u8>,
+    pub event: Option<u8>,
+}
+
+/// Returns the NPCs placed on a location.
+pub fn get_location_npcs(rom: &rom::Rom, location: usize) -> Result<Vec<NpcPlacement>, Error> {
+    let record = rom.record(RecordId::NpcPlacementData)?;
+    let offset =
+        usize::from(rom.parse_record(RecordId::NpcPlacementPointer, location, parse_pointer)?);
+    let data = rom.read_bytes(RecordId::NpcPlacementData, 0)?;
+
+    data.get(offset..)
+        .and_then(|input| parse_npc_placements(input).ok())
+        .map(|(_, placements)| placements)
+        .ok_or(Error::Parse {
+            record: record.name,
+            address: record.address + offset,
+        })
+}
+
+pub fn parse_npc_placements(input: &[u8]) -> IResult<&[u8], Vec<NpcPlacement>> {
+    let (input, length) = le_u8(input)?;
+    count(parse_npc_placement, length.into())(input)
+}
+
+pub fn parse_npc_placement(input: &[u8]) -> IResult<&[u8], NpcPlacement> {
+    let (input, sprite) = le_u8(input)?;
+    let (input, x) = le_u8(input)?;
+    let (input, y) = le_u8(input)?;
+    let (input, flags) = le_u8(input)?;
+    let (input, visibility_flag) = le_u8(input)?;
+    let (input, event) = le_u8(input)?;
+
+    let movement = match (flags >> FLAGS_MOVEMENT_SHIFT) & FLAGS_MOVEMENT {
+        0 => Movement::Still,
+        1 => Movement::Wander,
+        2 => Movement::PaceHorizontal,
+        3 => Movement::PaceVertical,
+        4 => Movement::Spin,
+        movement => Movement::Unknown(movement),
+    };
+
+    Ok((
+        input,
+        NpcPlacement {
+            sprite,
+            x,
+            y,
+            facing: Facing::new(flags),
+            movement,
+            intangible: flags & FLAGS_INTANGIBLE > 0,
+            visibility_flag: Some(visibility_flag).filter(|&flag| flag != ALWAYS_VISIBLE),
+            event: Some(event).filter(|&event| event != NO_EVENT),
+        },
+    ))
+}
+
+fn parse_pointer(input: &[u8]) -> IResult<&[u8], u16> {
+    le_u16(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_parse_npc_placement() {
+        assert_eq!(
+            parse_npc_placement(&[0x12, 0x05, 0x07, 0x86, 0xFF, 0x20]),
+            Ok((
+                &[][..],
+                NpcPlacement {
+                    sprite: 0x12,
+                    x: 5,
+                    y: 7,
+                    facing: Facing::Down,
+                    movement: Movement::Wander,
+                    intangible: true,
+                    visibility_flag: None,
+                    event: Some(0x20),
+                }
+            ))
+        );
+        assert_eq!(
+            parse_npc_placement(&[0x12, 0x05, 0x07, 0x14, 0xFF, 0x20])
+                .unwrap()
+                .1
+                .movement,
+            Movement::Unknown(5)
+        );
+    }
+
+    #[test]
+    fn test_get_location_npcs() {
+        let mut data = vec![0; 0x08];
+        data.extend_from_slice(&[0x01, 0x30, 0x02, 0x03, 0x0B, 0x40, 0xFF]);
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::NpcPlacementPointer, 0x15, &[0x08, 0x00])
+            .write(RecordId::NpcPlacementData, 0, &data)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            get_location_npcs(&rom, 0x15).unwrap(),
+            [NpcPlacement {
+                sprite: 0x30,
+                x: 2,
+                y: 3,
+                facing: Facing::Left,
+                movement: Movement::PaceHorizontal,
+                intangible: false,
+                visibility_flag: Some(0x40),
+                event: None,
+            }]
+        );
+        assert!(get_location_npcs(&rom, 0x16).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,417 @@
+use std::fmt;
+
+use miniz_oxide::inflate;
+
+use crate::rom::crc32;
+use crate::rom::header::RomHeader;
+use crate::rom::mapper::LoRom;
+use crate::Error;
+
+const COPIER_HEADER_LENGTH: usize = 0x200;
+const INTERLEAVE_BLOCK_SIZE: usize = 0x8000;
+
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+const GZIP_METHOD_DEFLATE: u8 = 8;
+const GZIP_FLAG_HEADER_CRC: u8 = 0x02;
+const GZIP_FLAG_EXTRA: u8 = 0x04;
+const GZIP_FLAG_NAME: u8 = 0x08;
+const GZIP_FLAG_COMMENT: u8 = 0x10;
+
+const ZIP_LOCAL_HEADER_MAGIC: &[u8] = b"PK\x03\x04";
+const ZIP_CENTRAL_HEADER_MAGIC: &[u8] = b"PK\x01\x02";
+const ZIP_END_OF_DIRECTORY_MAGIC: &[u8] = b"PK\x05\x06";
+const ZIP_METHOD_STORED: u16 = 0;
+const ZIP_METHOD_DEFLATE: u16 = 8;
+const ZIP_ROM_EXTENSIONS: [&str; 5] = [".sfc", ".smc", ".swc", ".fig", ".bin"];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Archive {
+    Gzip,
+    Zip,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Normalization {
+    Extracted(Archive),
+    RemovedCopierHeader,
+    Deinterleaved,
+}
+
+impl fmt::Display for Normalization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Normalization::Extracted(Archive::Gzip) => write!(f, "extracting from gzip archive"),
+            Normalization::Extracted(Archive::Zip) => write!(f, "extracting from zip archive"),
+            Normalization::RemovedCopierHeader => write!(f, "removing copier header"),
+            Normalization::Deinterleaved => write!(f, "de-interleaving"),
+        }
+    }
+}
+
+pub(crate) struct Image {
+    pub data: Vec<u8>,
+    pub copier_header: Option<Vec<u8>>,
+    pub normalizations: Vec<Normalization>,
+    // Steps that were tried but left the image without a plausible internal header.
+    pub failed: Vec<Normalization>,
+}
+
+pub(crate) fn normalize(data: Vec<u8>) -> Result<Image, Error> {
+    let mut normalizations = Vec::new();
+
+    let mut data = if data.starts_with(GZIP_MAGIC) {
+        normalizations.push(Normalization::Extracted(Archive::Gzip));
+        extract_gzip(&data)?
+    } else if data.starts_with(ZIP_LOCAL_HEADER_MAGIC) {
+        normalizations.push(Normalization::Extracted(Archive::Zip));
+        extract_zip(&data)?
+    } else {
+        data
+    };
+
+    let copier_header = if data.len() % INTERLEAVE_BLOCK_SIZE == COPIER_HEADER_LENGTH {
+        normalizations.push(Normalization::RemovedCopierHeader);
+        Some(data.drain(..COPIER_HEADER_LENGTH).collect())
+    } else {
+        None
+    };
+
+    let mut failed = Vec::new();
+
+    if !has_plausible_header(&data) {
+        match deinterleave(&data) {
+            Some(deinterleaved) if has_plausible_header(&deinterleaved) => {
+                normalizations.push(Normalization::Deinterleaved);
+                data = deinterleaved;
+            }
+            _ => {
+                if copier_header.is_some() {
+                    failed.push(Normalization::RemovedCopierHeader);
+                }
+
+                failed.push(Normalization::Deinterleaved);
+            }
+        }
+    }
+
+    Ok(Image {
+        data,
+        copier_header,
+        normalizations,
+        failed,
+    })
+}
+
+fn has_plausible_header(data: &[u8]) -> bool {
+    matches!(
+        RomHeader::from_image(data, &LoRom::new(data.len())),
+        Some(header) if header.is_plausible()
+    )
+}
+
+// Interleaved dumps store the second half of the image in the even 32 KiB blocks and the first
+// half in the odd ones.
+fn deinterleave(data: &[u8]) -> Option<Vec<u8>> {
+    let block_count = data.len() / INTERLEAVE_BLOCK_SIZE;
+
+    if block_count * INTERLEAVE_BLOCK_SIZE != data.len() || block_count < 2 || block_count % 2 == 1
+    {
+        return None;
+    }
+
+    let half = block_count / 2;
+
+    Some(
+        (0..block_count)
+            .flat_map(|block| {
+                let source = if block % 2 == 0 {
+                    half + block / 2
+                } else {
+                    block / 2
+                };
+
+                data[source * INTERLEAVE_BLOCK_SIZE..(source + 1) * INTERLEAVE_BLOCK_SIZE].iter()
+            })
+            .copied()
+            .collect(),
+    )
+}
+
+fn extract_gzip(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let truncated = || Error::InvalidArchive("truncated gzip archive");
+
+    if data.len() < 18 || data[2] != GZIP_METHOD_DEFLATE {
+        return Err(Error::InvalidArchive("unsupported gzip archive"));
+    }
+
+    let flags = data[3];
+    let mut position = 10;
+
+    if flags & GZIP_FLAG_EXTRA > 0 {
+        let length = usize::from(*data.get(position).ok_or_else(truncated)?)
+            | usize::from(*data.get(position + 1).ok_or_else(truncated)?) << 8;
+        position += 2 + length;
+    }
+
+    for flag in [GZIP_FLAG_NAME, GZIP_FLAG_COMMENT].iter() {
+        if flags & flag > 0 {
+            let length = data
+                .get(position..)
+                .and_then(|bytes| bytes.iter().position(|&byte| byte == 0))
+                .ok_or_else(truncated)?;
+            position += length + 1;
+        }
+    }
+
+    if flags & GZIP_FLAG_HEADER_CRC > 0 {
+        position += 2;
+    }
+
+    let footer = data.len() - 8;
+    let compressed = data.get(position..footer).ok_or_else(truncated)?;
+
+    let output = inflate::decompress_to_vec(compressed)
+        .map_err(|_| Error::InvalidArchive("corrupt gzip data"))?;
+
+    if crc32(&output) != read_u32(data, footer) {
+        return Err(Error::InvalidArchive("gzip checksum mismatch"));
+    }
+
+    Ok(output)
+}
+
+fn extract_zip(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let truncated = || Error::InvalidArchive("truncated zip archive");
+
+    let end = (0..data.len().saturating_sub(21))
+        .rev()
+        .find(|&position| data[position..].starts_with(ZIP_END_OF_DIRECTORY_MAGIC))
+        .ok_or_else(truncated)?;
+
+    let entry_count = usize::from(read_u16(data, end + 10));
+    let mut position = read_u32(data, end + 16) as usize;
+    let mut entries = Vec::with_capacity(entry_count);
+
+    for _ in 0..entry_count {
+        if !matches!(data.get(position..), Some(bytes) if bytes.starts_with(ZIP_CENTRAL_HEADER_MAGIC))
+        {
+            return Err(truncated());
+        }
+
+        let name_length = usize::from(read_u16(data, position + 28));
+        let extra_length = usize::from(read_u16(data, position + 30));
+        let comment_length = usize::from(read_u16(data, position + 32));
+
+        let name = data
+            .get(position + 46..position + 46 + name_length)
+            .ok_or_else(truncated)?;
+
+        entries.push(ZipEntry {
+            name: String::from_utf8_lossy(name).to_lowercase(),
+            method: read_u16(data, position + 10),
+            crc32: read_u32(data, position + 16),
+            compressed_size: read_u32(data, position + 20) as usize,
+            size: read_u32(data, position + 24) as usize,
+            offset: read_u32(data, position + 42) as usize,
+        });
+
+        position += 46 + name_length + extra_length + comment_length;
+    }
+
+    let entry = entries
+        .iter()
+        .filter(|entry| {
+            ZIP_ROM_EXTENSIONS
+                .iter()
+                .any(|extension| entry.name.ends_with(extension))
+        })
+        .max_by_key(|entry| entry.size)
+        .or_else(|| entries.iter().max_by_key(|entry| entry.size))
+        .ok_or(Error::InvalidArchive("empty zip archive"))?;
+
+    let header = entry.offset;
+    let start = header
+        + 30
+        + usize::from(read_u16(data, header + 26))
+        + usize::from(read_u16(data, header + 28));
+
+    let compressed = data
+        .get(start..start + entry.compressed_size)
+        .ok_or_else(truncated)?;
+
+    let output = match entry.method {
+        ZIP_METHOD_STORED => compressed.to_vec(),
+        ZIP_METHOD_DEFLATE => inflate::decompress_to_vec(compressed)
+            .map_err(|_| Error::InvalidArchive("corrupt zip data"))?,
+        _ => return Err(Error::InvalidArchive("unsupported zip compression method")),
+    };
+
+    if output.len() != entry.size || crc32(&output) != entry.crc32 {
+        return Err(Error::InvalidArchive("zip checksum mismatch"));
+    }
+
+    Ok(output)
+}
+
+struct ZipEntry {
+    name: String,
+    method: u16,
+    crc32: u32,
+    compressed_size: usize,
+    size: usize,
+    offset: usize,
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    data.get(offset..offset + 2)
+        .map_or(0, |bytes| u16::from(bytes[0]) | (u16::from(bytes[1]) << 8))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from(read_u16(data, offset)) | (u32::from(read_u16(data, offset + 2)) << 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plausible_image() -> Vec<u8> {
+        let mut data = vec![0; 0x20000];
+        data[0x7FC0..0x7FD5].copy_from_slice(b"FINAL FANTASY II     ");
+        data[0x7FD5] = 0x20;
+        data[0x7FDC..0x7FE0].copy_from_slice(&[0xFF, 0xFF, 0x00, 0x00]);
+        data[0x10000] = 0x42;
+        data
+    }
+
+    fn interleave(data: &[u8]) -> Vec<u8> {
+        let half = data.len() / INTERLEAVE_BLOCK_SIZE / 2;
+
+        (0..half * 2)
+            .flat_map(|block| {
+                let source = if block < half {
+                    block * 2 + 1
+                } else {
+                    (block - half) * 2
+                };
+
+                data[source * INTERLEAVE_BLOCK_SIZE..(source + 1) * INTERLEAVE_BLOCK_SIZE].iter()
+            })
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_copier_header() {
+        let image = plausible_image();
+        let mut data = vec![0xAA; COPIER_HEADER_LENGTH];
+        data.extend_from_slice(&image);
+
+        let normalized = normalize(data).unwrap();
+
+        assert_eq!(normalized.data, image);
+        assert_eq!(
+            normalized.copier_header,
+            Some(vec![0xAA; COPIER_HEADER_LENGTH])
+        );
+        assert_eq!(
+            normalized.normalizations,
+            [Normalization::RemovedCopierHeader]
+        );
+        assert!(normalized.failed.is_empty());
+
+        let mut data = vec![0xAA; COPIER_HEADER_LENGTH];
+        data.resize(COPIER_HEADER_LENGTH + 0x20000, 0);
+
+        let normalized = normalize(data).unwrap();
+        assert_eq!(normalized.data.len(), 0x20000);
+        assert_eq!(
+            normalized.failed,
+            [
+                Normalization::RemovedCopierHeader,
+                Normalization::Deinterleaved
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_interleaved() {
+        let image = plausible_image();
+        let normalized = normalize(interleave(&image)).unwrap();
+
+        assert_eq!(normalized.data, image);
+        assert_eq!(normalized.normalizations, [Normalization::Deinterleaved]);
+
+        let normalized = normalize(image.clone()).unwrap();
+        assert_eq!(normalized.data, image);
+        assert!(normalized.normalizations.is_empty());
+        assert!(normalized.failed.is_empty());
+
+        let normalized = normalize(vec![0; 0x20000]).unwrap();
+        assert_eq!(normalized.data, vec![0; 0x20000]);
+        assert!(normalized.normalizations.is_empty());
+        assert_eq!(normalized.failed, [Normalization::Deinterleaved]);
+    }
+
+    #[test]
+    fn test_normalize_gzip() {
+        let image = plausible_image();
+
+        let mut data = vec![0x1F, 0x8B, 0x08, GZIP_FLAG_NAME, 0, 0, 0, 0, 0, 0xFF];
+        data.extend_from_slice(b"ff2.sfc\0");
+        data.extend(miniz_oxide::deflate::compress_to_vec(&image, 6));
+        data.extend_from_slice(&crc32(&image).to_le_bytes());
+        data.extend_from_slice(&(image.len() as u32).to_le_bytes());
+
+        let normalized = normalize(data.clone()).unwrap();
+        assert_eq!(normalized.data, image);
+        assert_eq!(
+            normalized.normalizations,
+            [Normalization::Extracted(Archive::Gzip)]
+        );
+
+        let length = data.len();
+        data[length - 8] ^= 0xFF;
+        assert!(matches!(normalize(data), Err(Error::InvalidArchive(_))));
+    }
+
+    #[test]
+    fn test_normalize_zip() {
+        let image = plausible_image();
+        let compressed = miniz_oxide::deflate::compress_to_vec(&image, 6);
+        let name = b"ff2.sfc";
+
+        let mut data = ZIP_LOCAL_HEADER_MAGIC.to_vec();
+        data.extend_from_slice(&[0; 22]);
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(name);
+        data.extend_from_slice(&compressed);
+
+        let directory = data.len();
+        data.extend_from_slice(ZIP_CENTRAL_HEADER_MAGIC);
+        data.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(&ZIP_METHOD_DEFLATE.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&crc32(&image).to_le_bytes());
+        data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(image.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(name);
+
+        data.extend_from_slice(ZIP_END_OF_DIRECTORY_MAGIC);
+        data.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&(directory as u32).to_le_bytes());
+        data.extend_from_slice(&[0; 2]);
+
+        let normalized = normalize(data).unwrap();
+        assert_eq!(normalized.data, image);
+        assert_eq!(
+            normalized.normalizations,
+            [Normalization::Extracted(Archive::Zip)]
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use nom::{
+    bytes::complete::take,
+    number::complete::{le_u16, le_u8},
+    IResult,
+};
+
+use crate::rom::mapper::Mapper;
+
+pub(crate) const HEADER_OFFSET: usize = 0x7FC0;
+pub(crate) const HEADER_LENGTH: usize = 0x20;
+
+pub(crate) const CHECKSUM_COMPLEMENT_OFFSET: usize = 0x1C;
+const TITLE_LENGTH: usize = 21;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MapMode {
+    LoRom,
+    HiRom,
+    ExLoRom,
+    ExHiRom,
+    Other(u8),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Region {
+    Japan,
+    NorthAmerica,
+    Europe,
+    Other(u8),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RomHeader {
+    pub title: String,
+    pub map_mode: MapMode,
+    pub fast_rom: bool,
+    pub cartridge_type: u8,
+    pub rom_size: usize,
+    pub sram_size: usize,
+    pub region: Region,
+    pub developer: u8,
+    pub version: u8,
+    pub checksum_complement: u16,
+    pub checksum: u16,
+}
+
+impl RomHeader {
+    pub fn from_image(data: &[u8], mapper: &dyn Mapper) -> Option<RomHeader> {
+        let offset = mapper.header_offset();
+        let bytes = data.get(offset..offset + HEADER_LENGTH)?;
+        parse_header(bytes).ok().map(|(_, header)| header)
+    }
+
+    pub fn is_checksum_consistent(&self) -> bool {
+        self.checksum ^ self.checksum_complement == 0xFFFF
+    }
+
+    /// Whether the header looks like a real SNES header rather than arbitrary ROM data.
+    pub fn is_plausible(&self) -> bool {
+        self.is_checksum_consistent()
+            && !matches!(self.map_mode, MapMode::Other(_))
+            && !self.title.is_empty()
+            && !self.title.contains('?')
+    }
+}
+
+pub fn parse_header(input: &[u8]) -> IResult<&[u8], RomHeader> {
+    let (input, title) = take(TITLE_LENGTH)(input)?;
+    let title = title
+        .iter()
+        .map(|&byte| {
+            if byte.is_ascii() {
+                char::from(byte)
+            } else {
+                '?'
+            }
+        })
+        .collect::<String>()
+        .trim_end()
+        .to_string();
+
+    let (input, map_mode) = le_u8(input)?;
+    let fast_rom = map_mode & 0x10 > 0;
+    let map_mode = match map_mode & 0xEF {
+        0x20 => MapMode::LoRom,
+        0x21 => MapMode::HiRom,
+        0x22 => MapMode::ExLoRom,
+        0x25 => MapMode::ExHiRom,
+        x => MapMode::Other(x),
+    };
+
+    let (input, cartridge_type) = le_u8(input)?;
+    let (input, rom_size) = le_u8(input)?;
+    let (input, sram_size) = le_u8(input)?;
+
+    let (input, region) = le_u8(input)?;
+    let region = match region {
+        0x00 => Region::Japan,
+        0x01 => Region::NorthAmerica,
+        0x02 => Region::Europe,
+        x => Region::Other(x),
+    };
+
+    let (input, developer) = le_u8(input)?;
+    let (input, version) = le_u8(input)?;
+    let (input, checksum_complement) = le_u16(input)?;
+    let (input, checksum) = le_u16(input)?;
+
+    Ok((
+        input,
+        RomHeader {
+            title,
+            map_mode,
+            fast_rom,
+            cartridge_type,
+            rom_size: size_from_exponent(rom_size),
+            sram_size: size_from_exponent(sram_size),
+            region,
+            developer,
+            version,
+            checksum_complement,
+            checksum,
+        },
+    ))
+}
+
+pub fn compute_checksum(data: &[u8], mapper: &dyn Mapper) -> u16 {
+    let header_offset = mapper.header_offset() + CHECKSUM_COMPLEMENT_OFFSET;
+
+    // The checksum fields are counted as if they held $FFFF and $0000, which is what a valid pair
+    // sums to anyway.
+    let stored = data
+        .get(header_offset..header_offset + 4)
+        .map_or(0, |bytes| bytes.iter().map(|&byte| u32::from(byte)).sum());
+
+    let sum = mirrored_sum(data).wrapping_sub(stored).wrapping_add(0x1FE);
+
+    (sum & 0xFFFF) as u16
+}
+
+fn mirrored_sum(data: &[u8]) -> u32 {
+    let sum = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .fold(0u32, |sum, &byte| sum.wrapping_add(u32::from(byte)))
+    };
+
+    if data.is_empty() || data.len().is_power_of_two() {
+        return sum(data);
+    }
+
+    // Images whose size is not a power of two have their remainder mirrored up to the next power
+    // of two, in the same way the cartridge address decoding would.
+    let base = data.len().next_power_of_two() / 2;
+    let remainder = &data[base..];
+    let repeat = (base / remainder.len()) as u32;
+
+    sum(&data[..base]).wrapping_add(mirrored_sum(remainder).wrapping_mul(repeat))
+}
+
+fn size_from_exponent(exponent: u8) -> usize {
+    match exponent {
+        0 => 0,
+        x if x < 16 => 0x400 << x,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::mapper::LoRom;
+
+    #[test]
+    fn test_parse_header() {
+        let mut data = b"FINAL FANTASY II     ".to_vec();
+        data.extend_from_slice(&[
+            0x20, 0x02, 0x0A, 0x03, 0x01, 0xC3, 0x01, 0x0F, 0x0F, 0xF0, 0xF0,
+        ]);
+
+        assert_eq!(
+            parse_header(&data),
+            Ok((
+                &[][..],
+                RomHeader {
+                    title: String::from("FINAL FANTASY II"),
+                    map_mode: MapMode::LoRom,
+                    fast_rom: false,
+                    cartridge_type: 0x02,
+                    rom_size: 0x100000,
+                    sram_size: 0x2000,
+                    region: Region::NorthAmerica,
+                    developer: 0xC3,
+                    version: 1,
+                    checksum_complement: 0x0F0F,
+                    checksum: 0xF0F0,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_compute_checksum() {
+        let mut data = vec![0; 0x10000];
+        data[0] = 0x12;
+        data[HEADER_OFFSET + CHECKSUM_COMPLEMENT_OFFSET..HEADER_OFFSET + HEADER_LENGTH]
+            .copy_from_slice(&[0x34, 0x12, 0x56, 0x78]);
+
+        assert_eq!(
+            compute_checksum(&data, &LoRom::new(data.len())),
+            0x12 + 0x1FE
+        );
+
+        data.extend_from_slice(&[0x00; 0x4000]);
+        data[0x10000] = 0x03;
+        assert_eq!(
+            compute_checksum(&data, &LoRom::new(data.len())),
+            0x12 + 0x1FE + 0x03 * 4
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use crate::rom::header::{self, MapMode};
+use crate::Error;
+
+const BANK_SIZE: usize = 0x8000;
+const LOROM_MAX_SIZE: usize = 0x400000;
+const EXLOROM_MAX_SIZE: usize = LOROM_MAX_SIZE + 0x7E * BANK_SIZE;
+const EXLOROM_HEADER_OFFSET: usize = LOROM_MAX_SIZE + header::HEADER_OFFSET;
+
+pub trait Mapper {
+    fn address_to_offset(&self, address: usize) -> Result<usize, Error>;
+    fn offset_to_address(&self, offset: usize) -> Result<usize, Error>;
+
+    fn header_offset(&self) -> usize {
+        self.address_to_offset(0x00FFC0)
+            .unwrap_or(header::HEADER_OFFSET)
+    }
+}
+
+pub struct LoRom {
+    size: usize,
+}
+
+impl LoRom {
+    pub fn new(size: usize) -> Self {
+        Self { size }
+    }
+}
+
+impl Mapper for LoRom {
+    fn address_to_offset(&self, address: usize) -> Result<usize, Error> {
+        let bank = address >> 16;
+
+        // Banks $7E and $7F are work RAM, but their $FE and $FF mirrors are still ROM.
+        if address > 0xFFFFFF || address & 0xFFFF < 0x8000 || bank == 0x7E || bank == 0x7F {
+            return Err(Error::UnmappedAddress(address));
+        }
+
+        let offset = ((bank & 0x7F) << 15) | (address & 0x7FFF);
+
+        if offset < self.size {
+            Ok(offset)
+        } else {
+            Err(Error::UnmappedAddress(address))
+        }
+    }
+
+    fn offset_to_address(&self, offset: usize) -> Result<usize, Error> {
+        if offset >= self.size || offset >= LOROM_MAX_SIZE {
+            return Err(Error::UnmappedOffset(offset));
+        }
+
+        let bank = offset / BANK_SIZE;
+        let bank = if bank >= 0x7E { bank | 0x80 } else { bank };
+
+        Ok((bank << 16) | 0x8000 | (offset % BANK_SIZE))
+    }
+}
+
+// ExLoROM maps the first 4 MiB of the image to banks $80-$FF and the remainder to banks $00-$7D,
+// which keeps an expanded game's original data and header at their usual $00-$3F addresses.
+pub struct ExLoRom {
+    size: usize,
+}
+
+impl ExLoRom {
+    pub fn new(size: usize) -> Self {
+        Self { size }
+    }
+}
+
+impl Mapper for ExLoRom {
+    fn address_to_offset(&self, address: usize) -> Result<usize, Error> {
+        let bank = address >> 16;
+
+        if address > 0xFFFFFF || address & 0xFFFF < 0x8000 || bank == 0x7E || bank == 0x7F {
+            return Err(Error::UnmappedAddress(address));
+        }
+
+        let offset = if bank >= 0x80 {
+            ((bank & 0x7F) << 15) | (address & 0x7FFF)
+        } else {
+            LOROM_MAX_SIZE + ((bank << 15) | (address & 0x7FFF))
+        };
+
+        if offset < self.size {
+            Ok(offset)
+        } else {
+            Err(Error::UnmappedAddress(address))
+        }
+    }
+
+    fn offset_to_address(&self, offset: usize) -> Result<usize, Error> {
+        if offset >= self.size || offset >= EXLOROM_MAX_SIZE {
+            return Err(Error::UnmappedOffset(offset));
+        }
+
+        let (bank, offset) = if offset < LOROM_MAX_SIZE {
+            (0x80 | (offset / BANK_SIZE), offset)
+        } else {
+            (
+                (offset - LOROM_MAX_SIZE) / BANK_SIZE,
+                offset - LOROM_MAX_SIZE,
+            )
+        };
+
+        Ok((bank << 16) | 0x8000 | (offset % BANK_SIZE))
+    }
+}
+
+pub fn detect(data: &[u8]) -> Box<dyn Mapper> {
+    if data.len() > LOROM_MAX_SIZE {
+        let map_mode = data
+            .get(EXLOROM_HEADER_OFFSET..EXLOROM_HEADER_OFFSET + header::HEADER_LENGTH)
+            .and_then(|bytes| header::parse_header(bytes).ok())
+            .map(|(_, header)| header.map_mode);
+
+        if matches!(map_mode, Some(MapMode::LoRom) | Some(MapMode::ExLoRom)) {
+            return Box::new(ExLoRom::new(data.len()));
+        }
+    }
+
+    Box::new(LoRom::new(data.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lorom() {
+        let mapper = LoRom::new(0x100000);
+
+        assert_eq!(mapper.address_to_offset(0x008000).unwrap(), 0x000000);
+        assert_eq!(mapper.address_to_offset(0x018000).unwrap(), 0x008000);
+        assert_eq!(mapper.address_to_offset(0x14FFFF).unwrap(), 0x0A7FFF);
+        assert_eq!(mapper.address_to_offset(0x158000).unwrap(), 0x0A8000);
+        assert_eq!(mapper.address_to_offset(0x15FFFF).unwrap(), 0x0AFFFF);
+        assert_eq!(mapper.address_to_offset(0x958000).unwrap(), 0x0A8000);
+
+        assert!(matches!(
+            mapper.address_to_offset(0x007FFF),
+            Err(Error::UnmappedAddress(0x007FFF))
+        ));
+        assert!(matches!(
+            mapper.address_to_offset(0x7E8000),
+            Err(Error::UnmappedAddress(_))
+        ));
+        assert!(matches!(
+            mapper.address_to_offset(0x208000),
+            Err(Error::UnmappedAddress(_))
+        ));
+
+        assert_eq!(mapper.offset_to_address(0x0A8000).unwrap(), 0x158000);
+        assert_eq!(mapper.offset_to_address(0x0AFFFF).unwrap(), 0x15FFFF);
+        assert!(matches!(
+            mapper.offset_to_address(0x100000),
+            Err(Error::UnmappedOffset(0x100000))
+        ));
+
+        let mapper = LoRom::new(0x400000);
+        assert_eq!(mapper.address_to_offset(0xFF8000).unwrap(), 0x3F8000);
+        assert_eq!(mapper.offset_to_address(0x3F8000).unwrap(), 0xFF8000);
+    }
+
+    #[test]
+    fn test_exlorom() {
+        let mapper = ExLoRom::new(0x600000);
+
+        assert_eq!(mapper.address_to_offset(0x808000).unwrap(), 0x000000);
+        assert_eq!(mapper.address_to_offset(0x00FFC0).unwrap(), 0x407FC0);
+        assert_eq!(mapper.address_to_offset(0x0FA900).unwrap(), 0x47A900);
+        assert!(matches!(
+            mapper.address_to_offset(0x408000),
+            Err(Error::UnmappedAddress(_))
+        ));
+
+        assert_eq!(mapper.offset_to_address(0x000000).unwrap(), 0x808000);
+        assert_eq!(mapper.offset_to_address(0x47A900).unwrap(), 0x0FA900);
+    }
+
+    #[test]
+    fn test_detect() {
+        let mut data = vec![0; 0x600000];
+        assert_eq!(detect(&data).header_offset(), 0x7FC0);
+
+        data[EXLOROM_HEADER_OFFSET + 0x15] = 0x30;
+        assert_eq!(detect(&data).header_offset(), EXLOROM_HEADER_OFFSET);
+    }
+}
@@ -0,0 +1,717 @@
+use std::fs;
+use std::str;
+
+use image::Rgba;
+use log::warn;
+use nom::IResult;
+use sha2::{Digest, Sha256};
+
+use crate::rom_map;
+use crate::rom_map::record::{Record, RecordId};
+use crate::Error;
+
+pub mod format;
+pub mod header;
+pub mod mapper;
+pub mod patch;
+
+pub use header::RomHeader;
+pub use mapper::Mapper;
+
+const ROM_SIZE: usize = 0x100000;
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub lenient: bool,
+    pub patches: Vec<Vec<u8>>,
+}
+
+pub struct Rom {
+    data: Vec<u8>,
+    mapper: Box<dyn Mapper>,
+    version: rom_map::Version,
+    modified: bool,
+    dirty: bool,
+    copier_header: Option<Vec<u8>>,
+}
+
+impl Rom {
+    pub fn new(filename: &str, options: &Options) -> Result<Rom, Error> {
+        Rom::from_bytes(fs::read(filename)?, options)
+    }
+
+    pub fn from_bytes(data: Vec<u8>, options: &Options) -> Result<Rom, Error> {
+        let format::Image {
+            data,
+            copier_header,
+            normalizations,
+            failed,
+        } = format::normalize(data)?;
+
+        for step in &failed {
+            warn!("No valid internal header found after {}", step);
+        }
+
+        Rom::from_image(data, copier_header, options).map_err(|err| {
+            if normalizations.is_empty() && failed.is_empty() {
+                err
+            } else {
+                Error::Normalized {
+                    normalizations,
+                    failed,
+                    source: Box::new(err),
+                }
+            }
+        })
+    }
+
+    fn from_image(
+        mut data: Vec<u8>,
+        copier_header: Option<Vec<u8>>,
+        options: &Options,
+    ) -> Result<Rom, Error> {
+        let mut mapper = mapper::detect(&data);
+        let (mut version, mut modified) = identify(&data, mapper.as_ref(), options.lenient)?;
+
+        if !options.patches.is_empty() {
+            for patch in &options.patches {
+                data = patch::apply(&data, patch)?;
+            }
+
+            // A patched image will rarely match a known hash, so once the source has been
+            // validated, identify the result the same way as any other modified image.
+            mapper = mapper::detect(&data);
+            let identified = identify(&data, mapper.as_ref(), true)?;
+            version = identified.0;
+            modified = identified.1;
+        }
+
+        Ok(Rom {
+            data,
+            mapper,
+            version,
+            modified,
+            dirty: false,
+            copier_header,
+        })
+    }
+
+    pub fn description(&self) -> String {
+        rom_map::get_description(self.version)
+    }
+
+    /// Returns whether the loaded image differs from the original release it was identified as.
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// Returns whether the image has been edited since it was loaded or last saved.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn title(&self) -> String {
+        self.header().title
+    }
+
+    pub fn header(&self) -> RomHeader {
+        // Every accepted image is at least as large as the original cartridge, so the header is
+        // always present.
+        RomHeader::from_image(&self.data, self.mapper.as_ref()).unwrap()
+    }
+
+    pub fn mapper(&self) -> &dyn Mapper {
+        self.mapper.as_ref()
+    }
+
+    pub fn compute_checksum(&self) -> u16 {
+        header::compute_checksum(&self.data, self.mapper.as_ref())
+    }
+
+    pub fn is_checksum_valid(&self) -> bool {
+        let header = self.header();
+        header.is_checksum_consistent() && header.checksum == self.compute_checksum()
+    }
+
+    pub fn fix_checksum(&mut self) {
+        let checksum = self.compute_checksum();
+        let offset = self.mapper.header_offset() + header::CHECKSUM_COMPLEMENT_OFFSET;
+
+        let bytes = [
+            !checksum as u8,
+            (!checksum >> 8) as u8,
+            checksum as u8,
+            (checksum >> 8) as u8,
+        ];
+
+        if self.data[offset..offset + 4] != bytes {
+            self.data[offset..offset + 4].copy_from_slice(&bytes);
+            self.dirty = true;
+        }
+    }
+
+    pub fn has_copier_header(&self) -> bool {
+        self.copier_header.is_some()
+    }
+
+    pub fn to_bytes(&self, copier_header: bool) -> Vec<u8> {
+        match &self.copier_header {
+            Some(header) if copier_header => [&header[..], &self.data[..]].concat(),
+            _ => self.data.clone(),
+        }
+    }
+
+    pub fn save(&mut self, filename: &str, copier_header: bool) -> Result<(), Error> {
+        self.fix_checksum();
+        fs::write(filename, self.to_bytes(copier_header))?;
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    pub fn record(&self, id: RecordId) -> Result<Record, Error> {
+        rom_map::get_record(self.version, id).ok_or(Error::UnmappedRecord {
+            record: id.name(),
+            version: self.version,
+        })
+    }
+
+    /// Returns the address of an entry in a record table. Entries are laid out contiguously in the
+    /// image, so a table that runs past the end of a bank continues at the start of the next one.
+    pub fn record_address(&self, id: RecordId, index: usize) -> Result<usize, Error> {
+        let record = self.record(id)?;
+        if index >= record.count {
+            return Err(Error::IndexOutOfRange {
+                record: record.name,
+                index,
+            });
+        }
+
+        let offset = self.mapper.address_to_offset(record.address)? + record.length * index;
+
+        self.mapper.offset_to_address(offset)
+    }
+
+    pub fn read_bytes(&self, id: RecordId, index: usize) -> Result<&[u8], Error> {
+        let record = self.record(id)?;
+        let address = self.record_address(id, index)?;
+        let offset = self.mapper.address_to_offset(address)?;
+
+        self.data
+            .get(offset..offset + record.length)
+            .ok_or(Error::Parse {
+                record: record.name,
+                address,
+            })
+    }
+
+    pub(crate) fn parse_record<T>(
+        &self,
+        id: RecordId,
+        index: usize,
+        parser: fn(&[u8]) -> IResult<&[u8], T>,
+    ) -> Result<T, Error> {
+        let record = self.record(id)?;
+        let address = self.record_address(id, index)?;
+        let bytes = self.read_bytes(id, index)?;
+
+        parser(bytes)
+            .map(|(_, result)| result)
+            .map_err(|_| Error::Parse {
+                record: record.name,
+                address,
+            })
+    }
+
+    /// Reads `count` consecutive palettes starting at `index`. A palette record's length is its size
+    /// in bytes, two per colour.
+    pub(crate) fn read_palette(
+        &self,
+        id: RecordId,
+        index: usize,
+        count: usize,
+    ) -> Result<Vec<Rgba<u8>>, Error> {
+        let record = self.record(id)?;
+        if index + count > record.count {
+            return Err(Error::IndexOutOfRange {
+                record: record.name,
+                index: index + count - 1,
+            });
+        }
+
+        let address = self.record_address(id, index)?;
+        let offset = self.mapper.address_to_offset(address)?;
+
+        let bytes = self
+            .data
+            .get(offset..offset + count * record.length)
+            .ok_or(Error::Parse {
+                record: record.name,
+                address,
+            })?;
+
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|color| snes_color_to_rgba(u16::from(color[0]) | (u16::from(color[1]) << 8)))
+            .collect())
+    }
+
+    pub fn read_u8(&self, address: usize) -> Result<u8, Error> {
+        let offset = self.mapper.address_to_offset(address)?;
+
+        self.data
+            .get(offset)
+            .copied()
+            .ok_or(Error::UnmappedAddress(address))
+    }
+
+    pub fn read_u16(&self, address: usize) -> Result<u16, Error> {
+        Ok(self.read_u8(address)? as u16 + ((self.read_u8(address + 1)? as u16) << 8))
+    }
+
+    pub fn write_bytes(&mut self, id: RecordId, index: usize, bytes: &[u8]) -> Result<(), Error> {
+        let record = self.record(id)?;
+        let address = self.record_address(id, index)?;
+
+        if bytes.len() > record.length {
+            return Err(Error::OutOfRange {
+                address,
+                length: bytes.len(),
+            });
+        }
+
+        self.write_at(address, bytes)
+    }
+
+    pub fn write_u8(&mut self, address: usize, value: u8) -> Result<(), Error> {
+        self.write_at(address, &[value])
+    }
+
+    pub fn write_u16(&mut self, address: usize, value: u16) -> Result<(), Error> {
+        self.write_at(address, &[value as u8, (value >> 8) as u8])
+    }
+
+    fn write_at(&mut self, address: usize, bytes: &[u8]) -> Result<(), Error> {
+        let offset = self.mapper.address_to_offset(address)?;
+
+        let target = self
+            .data
+            .get_mut(offset..offset + bytes.len())
+            .ok_or(Error::OutOfRange {
+                address,
+                length: bytes.len(),
+            })?;
+
+        target.copy_from_slice(bytes);
+        self.dirty = true;
+
+        Ok(())
+    }
+}
+
+fn snes_color_to_rgba(color: u16) -> Rgba<u8> {
+    let r = (color & 0x1F) as u8;
+    let g = ((color >> 5) & 0x1F) as u8;
+    let b = ((color >> 10) & 0x1F) as u8;
+
+    Rgba([r * 8 + r / 4, g * 8 + g / 4, b * 8 + b / 4, 255])
+}
+
+#[cfg(test)]
+pub(crate) struct RomBuilder {
+    data: Vec<u8>,
+    version: rom_map::Version,
+    verify_hash: bool,
+}
+
+#[cfg(test)]
+impl RomBuilder {
+    pub fn new(version: rom_map::Version) -> Self {
+        Self {
+            data: vec![0; ROM_SIZE],
+            version,
+            verify_hash: true,
+        }
+    }
+
+    pub fn skip_hash_check(mut self) -> Self {
+        self.verify_hash = false;
+        self
+    }
+
+    pub fn write(mut self, id: RecordId, index: usize, bytes: &[u8]) -> Self {
+        let record = rom_map::get_record(self.version, id).expect("record is not mapped");
+        assert!(index < record.count, "index past the end of the table");
+        let offset = mapper::LoRom::new(ROM_SIZE)
+            .address_to_offset(record.address)
+            .unwrap()
+            + record.length * index;
+
+        self.data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        self
+    }
+
+    pub fn build(self) -> Result<Rom, Error> {
+        if self.verify_hash {
+            Rom::from_bytes(self.data, &Options::default())
+        } else {
+            Ok(Rom {
+                mapper: mapper::detect(&self.data),
+                data: self.data,
+                version: self.version,
+                modified: false,
+                dirty: false,
+                copier_header: None,
+            })
+        }
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Identifies an image by its hash, or by its internal header if `lenient` is set. Returns the
+/// version and whether the image is a modified copy of it.
+fn identify(
+    data: &[u8],
+    mapper: &dyn Mapper,
+    lenient: bool,
+) -> Result<(rom_map::Version, bool), Error> {
+    if data.len() != ROM_SIZE && !(lenient && data.len() > ROM_SIZE) {
+        return Err(Error::BadSize(data.len()));
+    }
+
+    let hash = hex::encode(Sha256::new().chain(data).finalize());
+
+    if let Some(version) = rom_map::get_version(&hash) {
+        return Ok((version, false));
+    }
+
+    if let Some(version) = rom_map::get_crc32_version(crc32(data)) {
+        if has_original_header(data, mapper, version) {
+            return Ok((version, false));
+        }
+    }
+
+    if lenient {
+        if let Some(version) = RomHeader::from_image(data, mapper)
+            .and_then(|header| rom_map::identify_version(&header))
+        {
+            warn!(
+                "Unrecognized ROM image (SHA-256: {}); treating it as a modified {}",
+                hash,
+                rom_map::get_description(version)
+            );
+
+            return Ok((version, true));
+        }
+    }
+
+    Err(Error::UnknownHash(hash))
+}
+
+// Returns whether an image's internal header names a version and carries a checksum that matches
+// the image, as an unmodified dump of that version does.
+fn has_original_header(data: &[u8], mapper: &dyn Mapper, version: rom_map::Version) -> bool {
+    match RomHeader::from_image(data, mapper) {
+        Some(header) => {
+            rom_map::identify_version(&header) == Some(version)
+                && header.is_checksum_consistent()
+                && header.checksum == header::compute_checksum(data, mapper)
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Blank test images have no internal header, so load errors also report the failed attempt to
+    // de-interleave them.
+    fn without_normalization(err: Error) -> Error {
+        match err {
+            Error::Normalized { source, .. } => *source,
+            err => err,
+        }
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let err = Rom::from_bytes(vec![0; 0x80000], &Options::default()).err();
+        assert!(matches!(
+            &err,
+            Some(Error::Normalized { normalizations, failed, .. })
+                if normalizations.is_empty() && failed == &[format::Normalization::Deinterleaved]
+        ));
+        assert!(matches!(
+            err.map(without_normalization),
+            Some(Error::BadSize(0x80000))
+        ));
+
+        assert!(matches!(
+            RomBuilder::new(rom_map::Version::Us)
+                .build()
+                .map_err(without_normalization),
+            Err(Error::UnknownHash(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_bytes_lenient() {
+        let mut data = vec![0; ROM_SIZE];
+        data[0x7FD9] = 0x01;
+        data[0x7FDB] = 0x01;
+
+        let options = Options {
+            lenient: true,
+            ..Options::default()
+        };
+
+        assert!(matches!(
+            Rom::from_bytes(data.clone(), &Options::default()).map_err(without_normalization),
+            Err(Error::UnknownHash(_))
+        ));
+
+        let rom = Rom::from_bytes(data.clone(), &options).unwrap();
+        assert_eq!(rom.version, rom_map::Version::UsRevA);
+        assert!(rom.is_modified());
+
+        data.resize(ROM_SIZE * 2, 0);
+        let rom = Rom::from_bytes(data.clone(), &options).unwrap();
+        assert_eq!(rom.version, rom_map::Version::UsRevA);
+
+        data[0x7FD9] = 0x02;
+        assert!(matches!(
+            Rom::from_bytes(data, &options).map_err(without_normalization),
+            Err(Error::UnknownHash(_))
+        ));
+    }
+
+    #[test]
+    fn test_header() {
+        let mut data = vec![0; ROM_SIZE];
+        data[0x7FC0..0x7FD5].copy_from_slice(b"FINAL FANTASY II     ");
+        data[0x7FD9] = 0x01;
+        data[0x7FDC..0x7FE0].copy_from_slice(&[0xFF, 0xFF, 0x00, 0x00]);
+        data[0x8000] = 0x42;
+
+        let options = Options {
+            lenient: true,
+            ..Options::default()
+        };
+
+        let mut rom = Rom::from_bytes(data, &options).unwrap();
+        let checksum = 0x42
+            + 0x1FE
+            + 0x01
+            + b"FINAL FANTASY II     "
+                .iter()
+                .map(|&x| x as u16)
+                .sum::<u16>();
+
+        assert_eq!(rom.title(), "FINAL FANTASY II");
+        assert_eq!(rom.header().region, header::Region::NorthAmerica);
+        assert_eq!(rom.compute_checksum(), checksum);
+        assert!(!rom.is_checksum_valid());
+        assert!(!rom.is_dirty());
+
+        rom.fix_checksum();
+        assert!(rom.is_checksum_valid());
+        assert!(rom.is_dirty());
+        assert_eq!(rom.header().checksum, checksum);
+        assert_eq!(rom.header().checksum_complement, !checksum);
+    }
+
+    #[test]
+    fn test_has_original_header() {
+        let mut data = vec![0; ROM_SIZE];
+        data[0x7FC0..0x7FD5].copy_from_slice(b"FINAL FANTASY 4      ");
+        data[0x8000] = 0x42;
+
+        let options = Options {
+            lenient: true,
+            ..Options::default()
+        };
+
+        let mut rom = Rom::from_bytes(data, &options).unwrap();
+        rom.fix_checksum();
+
+        let mut data = rom.to_bytes(false);
+        let mapper = mapper::detect(&data);
+
+        assert!(has_original_header(
+            &data,
+            mapper.as_ref(),
+            rom_map::Version::Japan
+        ));
+        assert!(!has_original_header(
+            &data,
+            mapper.as_ref(),
+            rom_map::Version::JapanRevA
+        ));
+
+        data[0x8000] = 0x43;
+        assert!(!has_original_header(
+            &data,
+            mapper.as_ref(),
+            rom_map::Version::Japan
+        ));
+    }
+
+    #[test]
+    fn test_write() {
+        let mut data = vec![0xAA; 512];
+        data.resize(512 + ROM_SIZE, 0);
+        // Only the North American releases have been mapped.
+        data[512 + 0x7FD9] = 0x01;
+
+        let options = Options {
+            lenient: true,
+            ..Options::default()
+        };
+
+        let mut rom = Rom::from_bytes(data, &options).unwrap();
+        assert!(rom.has_copier_header());
+        assert!(!rom.is_dirty());
+
+        rom.write_bytes(RecordId::CharacterStatsInitial, 2, &[0x01, 0x02])
+            .unwrap();
+        rom.write_u16(0x018000, 0x1234).unwrap();
+        rom.write_u8(0x018002, 0x56).unwrap();
+        assert!(rom.is_dirty());
+
+        assert_eq!(
+            rom.read_bytes(RecordId::CharacterStatsInitial, 2).unwrap()[0..3],
+            [0x01, 0x02, 0x00]
+        );
+        assert_eq!(rom.read_u16(0x018000).unwrap(), 0x1234);
+        assert_eq!(rom.read_u8(0x018002).unwrap(), 0x56);
+        assert!(matches!(
+            rom.read_u8(0x007FFF),
+            Err(Error::UnmappedAddress(0x007FFF))
+        ));
+
+        assert!(matches!(
+            rom.write_bytes(RecordId::CharacterStatsInitial, 0, &[0; 0x21]),
+            Err(Error::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            rom.write_u8(0x3F8000, 0),
+            Err(Error::UnmappedAddress(0x3F8000))
+        ));
+
+        let bytes = rom.to_bytes(true);
+        assert_eq!(bytes.len(), 512 + ROM_SIZE);
+        assert_eq!(bytes[0], 0xAA);
+        assert_eq!(bytes[512 + 0x8000..512 + 0x8003], [0x34, 0x12, 0x56]);
+        assert_eq!(rom.to_bytes(false).len(), ROM_SIZE);
+    }
+
+    #[test]
+    fn test_unmapped_record() {
+        let rom = RomBuilder::new(rom_map::Version::Japan)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            rom.read_bytes(RecordId::CharacterStatsInitial, 0),
+            Err(Error::UnmappedRecord {
+                record: "character initial stats",
+                version: rom_map::Version::Japan,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_dirty() {
+        let mut rom = RomBuilder::new(rom_map::Version::Us)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+        assert!(!rom.is_dirty());
+
+        rom.write_u8(0x018000, 0x01).unwrap();
+        assert!(rom.is_dirty());
+        assert!(!rom.is_modified());
+    }
+
+    #[test]
+    fn test_from_bytes_patched() {
+        let mut patch = b"PATCH".to_vec();
+        patch.extend_from_slice(&[0x00, 0x7F, 0xD9, 0x00, 0x01, 0x01]);
+        patch.extend_from_slice(b"EOF");
+
+        let options = Options {
+            patches: vec![patch],
+            ..Options::default()
+        };
+
+        // The unpatched source must still pass the caller's checks.
+        assert!(matches!(
+            Rom::from_bytes(vec![0; ROM_SIZE], &options).map_err(without_normalization),
+            Err(Error::UnknownHash(_))
+        ));
+        assert!(matches!(
+            Rom::from_bytes(vec![0; ROM_SIZE * 2], &options).map_err(without_normalization),
+            Err(Error::BadSize(_))
+        ));
+
+        let options = Options {
+            lenient: true,
+            ..options
+        };
+
+        let rom = Rom::from_bytes(vec![0; ROM_SIZE], &options).unwrap();
+        assert_eq!(rom.version, rom_map::Version::Us);
+        assert!(rom.is_modified());
+    }
+
+    #[test]
+    fn test_read_bytes() {
+        let rom = RomBuilder::new(rom_map::Version::Us)
+            .write(RecordId::CharacterStatsInitial, 1, &[0x12, 0x34])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            rom.read_bytes(RecordId::CharacterStatsInitial, 1).unwrap()[0..3],
+            [0x12, 0x34, 0x00]
+        );
+        assert!(matches!(
+            rom.read_bytes(RecordId::TitleTiles, 1),
+            Err(Error::IndexOutOfRange { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_record_address() {
+        let rom = RomBuilder::new(rom_map::Version::Us)
+            .write(RecordId::IndoorTilesetGraphics, 4, &[0x56])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            rom.record_address(RecordId::IndoorTilesetGraphics, 3)
+                .unwrap(),
+            0x18E000
+        );
+        assert_eq!(
+            rom.record_address(RecordId::IndoorTilesetGraphics, 4)
+                .unwrap(),
+            0x198000
+        );
+        assert_eq!(rom.read_u8(0x198000).unwrap(), 0x56);
+
+        assert!(matches!(
+            rom.record_address(RecordId::IndoorTilesetGraphics, 8),
+            Err(Error::IndexOutOfRange { index: 8, .. })
+        ));
+    }
+}
@@ -0,0 +1,367 @@
+use std::convert::TryFrom;
+
+use crate::rom::crc32;
+use crate::Error;
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: &[u8] = b"EOF";
+const BPS_MAGIC: &[u8] = b"BPS1";
+const BPS_FOOTER_LENGTH: usize = 12;
+
+const BPS_SOURCE_READ: usize = 0;
+const BPS_TARGET_READ: usize = 1;
+const BPS_SOURCE_COPY: usize = 2;
+const BPS_TARGET_COPY: usize = 3;
+
+pub fn apply(data: &[u8], patch: &[u8]) -> Result<Vec<u8>, Error> {
+    if patch.starts_with(IPS_MAGIC) {
+        apply_ips(data, patch)
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(data, patch)
+    } else {
+        Err(Error::InvalidPatch("unrecognized patch format"))
+    }
+}
+
+pub fn apply_ips(data: &[u8], patch: &[u8]) -> Result<Vec<u8>, Error> {
+    if !patch.starts_with(IPS_MAGIC) {
+        return Err(Error::InvalidPatch("missing IPS header"));
+    }
+
+    let mut output = data.to_vec();
+    let mut reader = PatchReader::new(&patch[IPS_MAGIC.len()..]);
+
+    loop {
+        if reader.remaining().starts_with(IPS_EOF) {
+            reader.skip(IPS_EOF.len())?;
+
+            if reader.remaining().len() == 3 {
+                output.truncate(reader.read_u24_be()?);
+            }
+
+            return Ok(output);
+        }
+
+        let offset = reader.read_u24_be()?;
+        let length = reader.read_u16_be()?;
+
+        let bytes = if length == 0 {
+            let count = reader.read_u16_be()?;
+            vec![reader.read_u8()?; count]
+        } else {
+            reader.read_bytes(length)?.to_vec()
+        };
+
+        if output.len() < offset + bytes.len() {
+            output.resize(offset + bytes.len(), 0);
+        }
+
+        output[offset..offset + bytes.len()].copy_from_slice(&bytes);
+    }
+}
+
+pub fn apply_bps(data: &[u8], patch: &[u8]) -> Result<Vec<u8>, Error> {
+    if !patch.starts_with(BPS_MAGIC) || patch.len() < BPS_MAGIC.len() + BPS_FOOTER_LENGTH {
+        return Err(Error::InvalidPatch("missing BPS header"));
+    }
+
+    let (body, footer) = patch.split_at(patch.len() - BPS_FOOTER_LENGTH);
+
+    let source_crc32 = read_u32_le(&footer[0..4]);
+    let target_crc32 = read_u32_le(&footer[4..8]);
+    let patch_crc32 = read_u32_le(&footer[8..12]);
+
+    check_crc32("patch", patch_crc32, crc32(&patch[..patch.len() - 4]))?;
+    check_crc32("source", source_crc32, crc32(data))?;
+
+    let mut reader = PatchReader::new(&body[BPS_MAGIC.len()..]);
+
+    let source_size = reader.read_varint()?;
+    let target_size = reader.read_varint()?;
+    let metadata_size = reader.read_varint()?;
+    reader.skip(metadata_size)?;
+
+    if source_size != data.len() {
+        return Err(Error::InvalidPatch(
+            "BPS source size does not match ROM image",
+        ));
+    }
+
+    // The target size comes from the patch, so it isn't trusted with an allocation up front.
+    let mut output = Vec::with_capacity(target_size.min(data.len() * 2));
+    let mut source_offset = 0;
+    let mut target_offset = 0;
+
+    while !reader.remaining().is_empty() {
+        let command = reader.read_varint()?;
+        let length = (command >> 2) + 1;
+
+        if length > target_size - output.len() {
+            return Err(Error::InvalidPatch("BPS output exceeds target size"));
+        }
+
+        match command & 0x03 {
+            BPS_SOURCE_READ => {
+                let position = output.len();
+                let bytes = data
+                    .get(position..position + length)
+                    .ok_or(Error::InvalidPatch("BPS source read out of range"))?;
+
+                output.extend_from_slice(bytes);
+            }
+            BPS_TARGET_READ => {
+                output.extend_from_slice(reader.read_bytes(length)?);
+            }
+            BPS_SOURCE_COPY => {
+                source_offset = apply_relative_offset(source_offset, reader.read_varint()?)?;
+
+                let bytes = data
+                    .get(source_offset..source_offset + length)
+                    .ok_or(Error::InvalidPatch("BPS source copy out of range"))?;
+
+                output.extend_from_slice(bytes);
+                source_offset += length;
+            }
+            BPS_TARGET_COPY => {
+                target_offset = apply_relative_offset(target_offset, reader.read_varint()?)?;
+
+                // The copied region may overlap the bytes being written, so copy one at a time.
+                for _ in 0..length {
+                    let byte = *output
+                        .get(target_offset)
+                        .ok_or(Error::InvalidPatch("BPS target copy out of range"))?;
+
+                    output.push(byte);
+                    target_offset += 1;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    if output.len() != target_size {
+        return Err(Error::InvalidPatch("BPS target size does not match output"));
+    }
+
+    check_crc32("target", target_crc32, crc32(&output))?;
+
+    Ok(output)
+}
+
+fn apply_relative_offset(offset: usize, encoded: usize) -> Result<usize, Error> {
+    let delta = i64::try_from(encoded >> 1).unwrap_or(i64::MAX);
+    let delta = if encoded & 0x01 > 0 { -delta } else { delta };
+
+    usize::try_from(offset as i64 + delta)
+        .map_err(|_| Error::InvalidPatch("BPS relative offset out of range"))
+}
+
+fn check_crc32(kind: &'static str, expected: u32, actual: u32) -> Result<(), Error> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(Error::PatchChecksum {
+            kind,
+            expected,
+            actual,
+        })
+    }
+}
+
+fn read_u32_le(data: &[u8]) -> u32 {
+    u32::from(data[0])
+        | (u32::from(data[1]) << 8)
+        | (u32::from(data[2]) << 16)
+        | (u32::from(data[3]) << 24)
+}
+
+struct PatchReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.data[self.position..]
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + length)
+            .ok_or(Error::InvalidPatch("unexpected end of patch"))?;
+
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, length: usize) -> Result<(), Error> {
+        self.read_bytes(length).map(|_| ())
+    }
+
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16_be(&mut self) -> Result<usize, Error> {
+        let bytes = self.read_bytes(2)?;
+        Ok((usize::from(bytes[0]) << 8) | usize::from(bytes[1]))
+    }
+
+    fn read_u24_be(&mut self) -> Result<usize, Error> {
+        let bytes = self.read_bytes(3)?;
+        Ok((usize::from(bytes[0]) << 16) | (usize::from(bytes[1]) << 8) | usize::from(bytes[2]))
+    }
+
+    fn read_varint(&mut self) -> Result<usize, Error> {
+        let mut value: u64 = 0;
+        let mut shift: u64 = 1;
+
+        loop {
+            let byte = u64::from(self.read_u8()?);
+            value += (byte & 0x7F) * shift;
+
+            if byte & 0x80 > 0 {
+                return usize::try_from(value)
+                    .map_err(|_| Error::InvalidPatch("BPS number out of range"));
+            }
+
+            if shift > 1 << 48 {
+                return Err(Error::InvalidPatch("BPS number out of range"));
+            }
+
+            shift <<= 7;
+            value += shift;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_varint(mut value: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+
+            if value == 0 {
+                bytes.push(byte | 0x80);
+                return bytes;
+            }
+
+            bytes.push(byte);
+            value -= 1;
+        }
+    }
+
+    fn build_bps(source: &[u8], target: &[u8], commands: &[u8]) -> Vec<u8> {
+        let mut patch = BPS_MAGIC.to_vec();
+        patch.extend(encode_varint(source.len()));
+        patch.extend(encode_varint(target.len()));
+        patch.extend(encode_varint(0));
+        patch.extend_from_slice(commands);
+        patch.extend_from_slice(&crc32(source).to_le_bytes());
+        patch.extend_from_slice(&crc32(target).to_le_bytes());
+        patch.extend_from_slice(&crc32(&patch).to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn test_apply_ips() {
+        let data = [0u8; 8];
+        let mut patch = IPS_MAGIC.to_vec();
+        patch.extend_from_slice(&[0x00, 0x00, 0x01, 0x00, 0x02, 0xAA, 0xBB]);
+        patch.extend_from_slice(&[0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x04, 0xCC]);
+        patch.extend_from_slice(IPS_EOF);
+
+        assert_eq!(
+            apply(&data, &patch).unwrap(),
+            [0x00, 0xAA, 0xBB, 0x00, 0x00, 0xCC, 0xCC, 0xCC, 0xCC]
+        );
+
+        patch.extend_from_slice(&[0x00, 0x00, 0x04]);
+        assert_eq!(apply(&data, &patch).unwrap(), [0x00, 0xAA, 0xBB, 0x00]);
+
+        assert!(matches!(
+            apply_ips(&data, &patch[..patch.len() - 6]),
+            Err(Error::InvalidPatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_apply_bps() {
+        let source = b"ABCDEFGH";
+        let target = b"ABCXYXYXYXEF";
+
+        let mut commands = Vec::new();
+        commands.extend(encode_varint((3 - 1) << 2 | BPS_SOURCE_READ));
+        commands.extend(encode_varint((2 - 1) << 2 | BPS_TARGET_READ));
+        commands.extend_from_slice(b"XY");
+        commands.extend(encode_varint((5 - 1) << 2 | BPS_TARGET_COPY));
+        commands.extend(encode_varint(3 << 1));
+        commands.extend(encode_varint((2 - 1) << 2 | BPS_SOURCE_COPY));
+        commands.extend(encode_varint(4 << 1));
+
+        let patch = build_bps(source, target, &commands);
+
+        assert_eq!(apply(source, &patch).unwrap(), target);
+
+        assert!(matches!(
+            apply(b"ABCDEFGX", &patch),
+            Err(Error::PatchChecksum { kind: "source", .. })
+        ));
+
+        let mut corrupted = patch.clone();
+        corrupted[6] ^= 0xFF;
+        assert!(matches!(
+            apply(source, &corrupted),
+            Err(Error::PatchChecksum { kind: "patch", .. })
+        ));
+    }
+
+    #[test]
+    fn test_apply_bps_past_target_size() {
+        let source = b"ABCD";
+        let target = b"AB";
+
+        let mut commands = Vec::new();
+        commands.extend(encode_varint((2 - 1) << 2 | BPS_SOURCE_READ));
+        commands.extend(encode_varint((0x10000 - 1) << 2 | BPS_TARGET_COPY));
+        commands.extend(encode_varint(0));
+
+        assert!(matches!(
+            apply(source, &build_bps(source, target, &commands)),
+            Err(Error::InvalidPatch("BPS output exceeds target size"))
+        ));
+
+        let mut commands = Vec::new();
+        commands.extend(encode_varint((3 - 1) << 2 | BPS_TARGET_READ));
+        commands.extend_from_slice(b"XYZ");
+
+        assert!(matches!(
+            apply(source, &build_bps(source, target, &commands)),
+            Err(Error::InvalidPatch("BPS output exceeds target size"))
+        ));
+        // A target size far beyond the output is rejected without allocating it.
+        let mut patch = BPS_MAGIC.to_vec();
+        patch.extend(encode_varint(source.len()));
+        patch.extend(encode_varint(1 << 40));
+        patch.extend(encode_varint(0));
+        patch.extend(encode_varint((4 - 1) << 2 | BPS_SOURCE_READ));
+        patch.extend_from_slice(&crc32(source).to_le_bytes());
+        patch.extend_from_slice(&crc32(source).to_le_bytes());
+        patch.extend_from_slice(&crc32(&patch).to_le_bytes());
+
+        assert!(matches!(
+            apply(source, &patch),
+            Err(Error::InvalidPatch("BPS target size does not match output"))
+        ));
+    }
+}
@@ -1,119 +1,389 @@
-pub(crate) mod record {
+pub mod record {
     #[derive(Copy, Clone)]
     pub struct Record {
+        pub name: &'static str,
         pub address: usize,
         pub length: usize,
+        /// The number of entries in the table.
+        pub count: usize,
     }
 
-    pub const CHARACTER_STATS_INITIAL: Record = Record {
-        address: 0x0FA900,
-        length: 0x20,
-    };
-
-    pub const OCEAN_ANIMATION_SEQUENCE: Record = Record {
-        address: 0x008E8C,
-        length: 0x10,
-    };
-
-    pub const WATERFALL_ANIMATION_SEQUENCE: Record = Record {
-        address: 0x008E7C,
-        length: 0x10,
-    };
-
-    pub const GAME_TITLE: Record = Record {
-        address: 0x00FFC0,
-        length: 21,
-    };
-
-    pub const OUTDOOR_TILEMAP_OVERWORLD: Record = Record {
-        address: 0x168480,
-        length: 0x4000,
-    };
-
-    pub const OUTDOOR_TILEMAP_UNDERWORLD: Record = Record {
-        address: 0x16C480,
-        length: 0x1D00,
-    };
-
-    pub const OUTDOOR_TILEMAP_MOON: Record = Record {
-        address: 0x16E180,
-        length: 0xA00,
-    };
-
-    pub const OUTDOOR_TILESET_PALETTE: Record = Record {
-        address: 0x148900,
-        length: 0x80,
-    };
-
-    pub const OUTDOOR_TILESET_COMPOSITION: Record = Record {
-        address: 0x148000,
-        length: 0x200,
-    };
-
-    pub const OUTDOOR_TILESET_UPPER_VALUES: Record = Record {
-        address: 0x148600,
-        length: 0x100,
-    };
-
-    pub const OUTDOOR_TILESET_LOWER_VALUES: Record = Record {
-        address: 0x1D8000,
-        length: 0x2000,
-    };
-
-    pub const OUTDOOR_TILE_PROPERTIES: Record = Record {
-        address: 0x148A80,
-        length: 0x100,
-    };
-
-    pub const FIELD_SPRITE_PALETTE_INDEX_PLAYER: Record = Record {
-        address: 0x15B2FA,
-        length: 0x01,
-    };
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub enum RecordId {
+        CharacterStatsInitial,
+        OceanAnimationSequence,
+        WaterfallAnimationSequence,
+        OutdoorTilemapOverworld,
+        OutdoorTilemapUnderworld,
+        OutdoorTilemapMoon,
+        OutdoorTilesetPalette,
+        OutdoorTilesetComposition,
+        OutdoorTilesetUpperValues,
+        OutdoorTilesetLowerValues,
+        OutdoorTileProperties,
+        FieldSpritePaletteIndexPlayer,
+        FieldSpritePalettePlayer,
+        FieldSpriteSheetPlayer,
+        FieldSpriteCompositionPlayer,
+        TitleTiles,
+        TitleTilemap,
+        TitlePalette,
+        TextDteTable,
+        TextDialoguePointer,
+        TextDialogue,
+        TextCharacterName,
+        ItemWeapon,
+        ItemArmor,
+        ItemConsumable,
+        ItemAttributes,
+        ItemEquipClasses,
+        ItemPrice,
+        TextItemName,
+        Spell,
+        SpellAnimation,
+        TextSpellName,
+        TextSummonName,
+        SpellSetPointer,
+        SpellSetData,
+        CharacterSpellSets,
+        MonsterPointer,
+        MonsterData,
+        MonsterStats,
+        MonsterSpeed,
+        MonsterDrops,
+        MonsterGil,
+        MonsterExperience,
+        TextMonsterName,
+        MonsterScriptPointer,
+        MonsterReactionPointer,
+        MonsterScriptData,
+        MonsterConditionSet,
+        MonsterCondition,
+        Formation,
+        FormationArrangement,
+        EncounterGroup,
+        EncounterRegion,
+        LocationEncounterGroup,
+        LevelUpTable,
+        ExperienceTable,
+        Shop,
+        LocationHeader,
+        LocationTilemapPointer,
+        LocationTilemapData,
+        IndoorTilesetComposition,
+        IndoorTileProperties,
+        IndoorTilesetPalette,
+        IndoorTilesetGraphics,
+        TriggerOutdoorPointer,
+        TriggerLocationPointer,
+        TriggerData,
+        FieldSpritePaletteIndexNpc,
+        FieldSpritePaletteNpc,
+        FieldSpriteSheetNpc,
+        FieldSpriteCompositionNpc,
+        NpcPlacementPointer,
+        NpcPlacementData,
+        EventPointer,
+        EventData,
+    }
 
-    pub const FIELD_SPRITE_PALETTE_PLAYER: Record = Record {
-        address: 0x0D8000,
-        length: 0x10,
-    };
+    impl RecordId {
+        pub const ALL: [RecordId; 75] = [
+            RecordId::CharacterStatsInitial,
+            RecordId::OceanAnimationSequence,
+            RecordId::WaterfallAnimationSequence,
+            RecordId::OutdoorTilemapOverworld,
+            RecordId::OutdoorTilemapUnderworld,
+            RecordId::OutdoorTilemapMoon,
+            RecordId::OutdoorTilesetPalette,
+            RecordId::OutdoorTilesetComposition,
+            RecordId::OutdoorTilesetUpperValues,
+            RecordId::OutdoorTilesetLowerValues,
+            RecordId::OutdoorTileProperties,
+            RecordId::FieldSpritePaletteIndexPlayer,
+            RecordId::FieldSpritePalettePlayer,
+            RecordId::FieldSpriteSheetPlayer,
+            RecordId::FieldSpriteCompositionPlayer,
+            RecordId::TitleTiles,
+            RecordId::TitleTilemap,
+            RecordId::TitlePalette,
+            RecordId::TextDteTable,
+            RecordId::TextDialoguePointer,
+            RecordId::TextDialogue,
+            RecordId::TextCharacterName,
+            RecordId::ItemWeapon,
+            RecordId::ItemArmor,
+            RecordId::ItemConsumable,
+            RecordId::ItemAttributes,
+            RecordId::ItemEquipClasses,
+            RecordId::ItemPrice,
+            RecordId::TextItemName,
+            RecordId::Spell,
+            RecordId::SpellAnimation,
+            RecordId::TextSpellName,
+            RecordId::TextSummonName,
+            RecordId::SpellSetPointer,
+            RecordId::SpellSetData,
+            RecordId::CharacterSpellSets,
+            RecordId::MonsterPointer,
+            RecordId::MonsterData,
+            RecordId::MonsterStats,
+            RecordId::MonsterSpeed,
+            RecordId::MonsterDrops,
+            RecordId::MonsterGil,
+            RecordId::MonsterExperience,
+            RecordId::TextMonsterName,
+            RecordId::MonsterScriptPointer,
+            RecordId::MonsterReactionPointer,
+            RecordId::MonsterScriptData,
+            RecordId::MonsterConditionSet,
+            RecordId::MonsterCondition,
+            RecordId::Formation,
+            RecordId::FormationArrangement,
+            RecordId::EncounterGroup,
+            RecordId::EncounterRegion,
+            RecordId::LocationEncounterGroup,
+            RecordId::LevelUpTable,
+            RecordId::ExperienceTable,
+            RecordId::Shop,
+            RecordId::LocationHeader,
+            RecordId::LocationTilemapPointer,
+            RecordId::LocationTilemapData,
+            RecordId::IndoorTilesetComposition,
+            RecordId::IndoorTileProperties,
+            RecordId::IndoorTilesetPalette,
+            RecordId::IndoorTilesetGraphics,
+            RecordId::TriggerOutdoorPointer,
+            RecordId::TriggerLocationPointer,
+            RecordId::TriggerData,
+            RecordId::FieldSpritePaletteIndexNpc,
+            RecordId::FieldSpritePaletteNpc,
+            RecordId::FieldSpriteSheetNpc,
+            RecordId::FieldSpriteCompositionNpc,
+            RecordId::NpcPlacementPointer,
+            RecordId::NpcPlacementData,
+            RecordId::EventPointer,
+            RecordId::EventData,
+        ];
 
-    pub const FIELD_SPRITE_SHEET_PLAYER: Record = Record {
-        address: 0x1B8000,
-        length: 0x0300,
-    };
+        pub fn name(self) -> &'static str {
+            match self {
+                RecordId::CharacterStatsInitial => "character initial stats",
+                RecordId::OceanAnimationSequence => "ocean animation sequence",
+                RecordId::WaterfallAnimationSequence => "waterfall animation sequence",
+                RecordId::OutdoorTilemapOverworld => "overworld tilemap",
+                RecordId::OutdoorTilemapUnderworld => "underworld tilemap",
+                RecordId::OutdoorTilemapMoon => "moon tilemap",
+                RecordId::OutdoorTilesetPalette => "outdoor tileset palette",
+                RecordId::OutdoorTilesetComposition => "outdoor tileset composition",
+                RecordId::OutdoorTilesetUpperValues => "outdoor tileset upper values",
+                RecordId::OutdoorTilesetLowerValues => "outdoor tileset lower values",
+                RecordId::OutdoorTileProperties => "outdoor tile properties",
+                RecordId::FieldSpritePaletteIndexPlayer => "player field sprite palette index",
+                RecordId::FieldSpritePalettePlayer => "player field sprite palette",
+                RecordId::FieldSpriteSheetPlayer => "player field sprite sheet",
+                RecordId::FieldSpriteCompositionPlayer => "player field sprite composition",
+                RecordId::TitleTiles => "title tiles",
+                RecordId::TitleTilemap => "title tilemap",
+                RecordId::TitlePalette => "title palette",
+                RecordId::TextDteTable => "text DTE table",
+                RecordId::TextDialoguePointer => "dialogue pointer",
+                RecordId::TextDialogue => "dialogue",
+                RecordId::TextCharacterName => "character name",
+                RecordId::ItemWeapon => "weapon",
+                RecordId::ItemArmor => "armor",
+                RecordId::ItemConsumable => "consumable item",
+                RecordId::ItemAttributes => "item attributes",
+                RecordId::ItemEquipClasses => "item equip classes",
+                RecordId::ItemPrice => "item price",
+                RecordId::TextItemName => "item name",
+                RecordId::Spell => "spell",
+                RecordId::SpellAnimation => "spell animation",
+                RecordId::TextSpellName => "spell name",
+                RecordId::TextSummonName => "summon name",
+                RecordId::SpellSetPointer => "spell set pointer",
+                RecordId::SpellSetData => "spell set data",
+                RecordId::CharacterSpellSets => "character spell sets",
+                RecordId::MonsterPointer => "monster pointer",
+                RecordId::MonsterData => "monster data",
+                RecordId::MonsterStats => "monster stat multipliers",
+                RecordId::MonsterSpeed => "monster speed",
+                RecordId::MonsterDrops => "monster drops",
+                RecordId::MonsterGil => "monster gil",
+                RecordId::MonsterExperience => "monster experience",
+                RecordId::TextMonsterName => "monster name",
+                RecordId::MonsterScriptPointer => "monster script pointer",
+                RecordId::MonsterReactionPointer => "monster reaction script pointer",
+                RecordId::MonsterScriptData => "monster script data",
+                RecordId::MonsterConditionSet => "monster condition set",
+                RecordId::MonsterCondition => "monster condition",
+                RecordId::Formation => "formation",
+                RecordId::FormationArrangement => "formation arrangement",
+                RecordId::EncounterGroup => "encounter group",
+                RecordId::EncounterRegion => "outdoor encounter regions",
+                RecordId::LocationEncounterGroup => "location encounter group",
+                RecordId::LevelUpTable => "level up table",
+                RecordId::ExperienceTable => "experience table",
+                RecordId::Shop => "shop",
+                RecordId::LocationHeader => "location header",
+                RecordId::LocationTilemapPointer => "location tilemap pointer",
+                RecordId::LocationTilemapData => "location tilemap data",
+                RecordId::IndoorTilesetComposition => "indoor tileset composition",
+                RecordId::IndoorTileProperties => "indoor tile properties",
+                RecordId::IndoorTilesetPalette => "indoor tileset palette",
+                RecordId::IndoorTilesetGraphics => "indoor tileset graphics",
+                RecordId::TriggerOutdoorPointer => "outdoor trigger table pointer",
+                RecordId::TriggerLocationPointer => "location trigger table pointer",
+                RecordId::TriggerData => "trigger data",
+                RecordId::FieldSpritePaletteIndexNpc => "NPC field sprite palette index",
+                RecordId::FieldSpritePaletteNpc => "NPC field sprite palette",
+                RecordId::FieldSpriteSheetNpc => "NPC field sprite sheet",
+                RecordId::FieldSpriteCompositionNpc => "NPC field sprite composition",
+                RecordId::NpcPlacementPointer => "NPC placement pointer",
+                RecordId::NpcPlacementData => "NPC placement data",
+                RecordId::EventPointer => "event script pointer",
+                RecordId::EventData => "event script data",
+            }
+        }
+    }
+}
 
-    pub const FIELD_SPRITE_COMPOSITION_PLAYER: Record = Record {
-        address: 0x15C0C4,
-        length: 0x08,
-    };
+use crate::graphics::FIELD_SPRITE_NPC_SHEET_COUNT;
+use crate::rom::header::{Region, RomHeader};
+use record::{Record, RecordId};
 
-    pub const TITLE_TILES: Record = Record {
-        address: 0x08C000,
-        length: 0x2000,
+/// Returns where a record is stored in a version, or `None` if that version hasn't been mapped.
+pub fn get_record(version: Version, id: RecordId) -> Option<Record> {
+    let (address, length, count) = match version {
+        Version::Us | Version::UsRevA => table_us(id),
+        Version::Japan | Version::JapanRevA | Version::EasyType => return None,
     };
 
-    pub const TITLE_TILEMAP: Record = Record {
-        address: 0x08E000,
-        length: 0x800,
-    };
+    Some(Record {
+        name: id.name(),
+        address,
+        length,
+        count,
+    })
+}
 
-    pub const TITLE_PALETTE: Record = Record {
-        address: 0x08E800,
-        length: 0x100,
-    };
+fn table_us(id: RecordId) -> (usize, usize, usize) {
+    match id {
+        RecordId::CharacterStatsInitial => (0x0FA900, 0x20, 0x20),
+        RecordId::OceanAnimationSequence => (0x008E8C, 0x10, 1),
+        RecordId::WaterfallAnimationSequence => (0x008E7C, 0x10, 1),
+        RecordId::OutdoorTilemapOverworld => (0x168480, 0x4000, 1),
+        RecordId::OutdoorTilemapUnderworld => (0x16C480, 0x1D00, 1),
+        RecordId::OutdoorTilemapMoon => (0x16E180, 0xA00, 1),
+        RecordId::OutdoorTilesetPalette => (0x148900, 0x80, 3),
+        RecordId::OutdoorTilesetComposition => (0x148000, 0x200, 3),
+        RecordId::OutdoorTilesetUpperValues => (0x148600, 0x100, 3),
+        RecordId::OutdoorTilesetLowerValues => (0x1D8000, 0x2000, 3),
+        RecordId::OutdoorTileProperties => (0x148A80, 0x100, 3),
+        RecordId::FieldSpritePaletteIndexPlayer => (0x15B2FA, 0x01, 0x2A),
+        RecordId::FieldSpritePalettePlayer => (0x0D8000, 0x10, 0x20),
+        RecordId::FieldSpriteSheetPlayer => (0x1B8000, 0x0300, 0x2A),
+        RecordId::FieldSpriteCompositionPlayer => (0x15C0C4, 0x08, 0x10),
+        RecordId::TitleTiles => (0x08C000, 0x2000, 1),
+        RecordId::TitleTilemap => (0x08E000, 0x800, 1),
+        RecordId::TitlePalette => (0x08E800, 0x200, 1),
+        RecordId::TextDteTable => (0x0EF000, 0x02, 0x80),
+        RecordId::TextDialoguePointer => (0x1E8000, 0x02, 0x200),
+        RecordId::TextDialogue => (0x1E8400, 0xBC00, 1),
+        RecordId::TextCharacterName => (0x0FA5C0, 0x06, 0x20),
+        RecordId::ItemWeapon => (0x0F9000, 0x08, 0x61),
+        RecordId::ItemArmor => (0x0F9308, 0x08, 0x4F),
+        RecordId::ItemConsumable => (0x0F95A0, 0x04, 0x50),
+        RecordId::ItemAttributes => (0x0F96E0, 0x04, 0x80),
+        RecordId::ItemEquipClasses => (0x0F98E0, 0x02, 0x40),
+        RecordId::ItemPrice => (0x0F9960, 0x01, 0x100),
+        RecordId::TextItemName => (0x0F8000, 0x09, 0x100),
+        RecordId::Spell => (0x0F8900, 0x06, 0x80),
+        RecordId::SpellAnimation => (0x0FD000, 0x01, 0x80),
+        RecordId::TextSpellName => (0x0F8C00, 0x06, 0x48),
+        RecordId::TextSummonName => (0x0F8DB0, 0x08, 0x10),
+        RecordId::SpellSetPointer => (0x0F8E30, 0x02, 0x10),
+        RecordId::SpellSetData => (0x0F8E50, 0x1B0, 1),
+        RecordId::CharacterSpellSets => (0x0FA680, 0x03, 0x20),
+        RecordId::MonsterPointer => (0x0E8000, 0x02, 0xE0),
+        RecordId::MonsterData => (0x0E81C0, 0x1E00, 1),
+        RecordId::MonsterStats => (0x0EA000, 0x03, 0x100),
+        RecordId::MonsterSpeed => (0x0EA300, 0x02, 0x40),
+        RecordId::MonsterDrops => (0x0EA380, 0x04, 0x40),
+        RecordId::MonsterGil => (0x0EA480, 0x02, 0xE0),
+        RecordId::MonsterExperience => (0x0EA640, 0x02, 0xE0),
+        RecordId::TextMonsterName => (0x0EA800, 0x08, 0xE0),
+        RecordId::MonsterScriptPointer => (0x0EB000, 0x02, 0x100),
+        RecordId::MonsterReactionPointer => (0x0EB200, 0x02, 0x80),
+        RecordId::MonsterScriptData => (0x0EB300, 0x2000, 1),
+        RecordId::MonsterConditionSet => (0x0ED300, 0x04, 0x100),
+        RecordId::MonsterCondition => (0x0ED700, 0x04, 0x100),
+        RecordId::Formation => (0x0EDB00, 0x08, 0x1C0),
+        RecordId::FormationArrangement => (0x0EE900, 0x08, 0x40),
+        RecordId::EncounterGroup => (0x0EEB00, 0x10, 0x40),
+        RecordId::EncounterRegion => (0x0EEF00, 0x40, 3),
+        RecordId::LocationEncounterGroup => (0x0EF100, 0x01, 0x180),
+        RecordId::LevelUpTable => (0x0FB000, 0x181, 0x0E),
+        RecordId::ExperienceTable => (0x0FC600, 0x03, 0x63),
+        RecordId::Shop => (0x0FC800, 0x09, 0x40),
+        RecordId::LocationHeader => (0x118000, 0x0A, 0x180),
+        RecordId::LocationTilemapPointer => (0x119000, 0x02, 0x100),
+        RecordId::LocationTilemapData => (0x119200, 0x6E00, 1),
+        RecordId::IndoorTilesetComposition => (0x108000, 0x400, 0x10),
+        RecordId::IndoorTileProperties => (0x10C000, 0x100, 0x10),
+        RecordId::IndoorTilesetPalette => (0x10E000, 0x100, 0x20),
+        RecordId::IndoorTilesetGraphics => (0x188000, 0x2000, 8),
+        RecordId::TriggerOutdoorPointer => (0x128000, 0x02, 3),
+        RecordId::TriggerLocationPointer => (0x128010, 0x02, 0x180),
+        RecordId::TriggerData => (0x128400, 0x3C00, 1),
+        RecordId::FieldSpritePaletteIndexNpc => (0x15B400, 0x01, FIELD_SPRITE_NPC_SHEET_COUNT),
+        RecordId::FieldSpritePaletteNpc => (0x0D8200, 0x10, 0x100),
+        RecordId::FieldSpriteSheetNpc => (0x1A8000, 0x0180, FIELD_SPRITE_NPC_SHEET_COUNT),
+        RecordId::FieldSpriteCompositionNpc => (0x15C144, 0x08, 8),
+        RecordId::NpcPlacementPointer => (0x138000, 0x02, 0x180),
+        RecordId::NpcPlacementData => (0x138400, 0x3C00, 1),
+        RecordId::EventPointer => (0x178000, 0x02, 0x100),
+        RecordId::EventData => (0x178400, 0x7C00, 1),
+    }
 }
 
 const HASH_USA: &str = "680535dc1c4196c53b40dc9c2c9bc159a77802ab8d4b474bef5dc0281c15ad06";
 const HASH_USA_REV_A: &str = "414bacc05a18a6137c0de060b4094ab6d1b75105342b0bb36a42e45d945a0e4d";
 
-#[derive(Copy, Clone)]
+// The CRC32 values published in the No-Intro database for the Japanese releases. A CRC32 match is
+// weak on its own, so an image is only identified by one when its internal header names the same
+// release and its checksum is intact.
+const CRC32_JAPAN: u32 = 0x2102_7C5D;
+const CRC32_JAPAN_REV_A: u32 = 0xCAA1_5E97;
+const CRC32_JAPAN_EASY_TYPE: u32 = 0x6CDA_700C;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Version {
     Us,
     UsRevA,
+    Japan,
+    JapanRevA,
+    EasyType,
 }
 
 pub fn get_description(version: Version) -> String {
     match version {
         Version::Us => String::from("Final Fantasy II (USA)"),
         Version::UsRevA => String::from("Final Fantasy II (USA) (Rev A)"),
+        Version::Japan => String::from("Final Fantasy IV (Japan)"),
+        Version::JapanRevA => String::from("Final Fantasy IV (Japan) (Rev A)"),
+        Version::EasyType => String::from("Final Fantasy IV Easy Type (Japan)"),
+    }
+}
+
+pub fn identify_version(header: &RomHeader) -> Option<Version> {
+    match (header.region, header.version) {
+        (Region::Japan, _) if header.title.contains("EASY") => Some(Version::EasyType),
+        (Region::Japan, 0) => Some(Version::Japan),
+        (Region::Japan, _) => Some(Version::JapanRevA),
+        (Region::NorthAmerica, 0) => Some(Version::Us),
+        (Region::NorthAmerica, _) => Some(Version::UsRevA),
+        _ => None,
     }
 }
 
@@ -124,3 +394,82 @@ pub fn get_version(hash: &str) -> Option<Version> {
         _ => None,
     }
 }
+
+/// Returns the release whose published CRC32 matches.
+pub fn get_crc32_version(crc32: u32) -> Option<Version> {
+    match crc32 {
+        CRC32_JAPAN => Some(Version::Japan),
+        CRC32_JAPAN_REV_A => Some(Version::JapanRevA),
+        CRC32_JAPAN_EASY_TYPE => Some(Version::EasyType),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::mapper::{LoRom, Mapper};
+
+    const VERSIONS: [Version; 5] = [
+        Version::Us,
+        Version::UsRevA,
+        Version::Japan,
+        Version::JapanRevA,
+        Version::EasyType,
+    ];
+
+    #[test]
+    fn test_get_version() {
+        assert_eq!(get_version(HASH_USA), Some(Version::Us));
+        assert_eq!(get_version(HASH_USA_REV_A), Some(Version::UsRevA));
+        assert_eq!(get_version(&"0".repeat(64)), None);
+
+        assert_eq!(get_crc32_version(CRC32_JAPAN), Some(Version::Japan));
+        assert_eq!(get_crc32_version(0), None);
+    }
+
+    #[test]
+    fn test_record_tables() {
+        let mapper = LoRom::new(0x100000);
+
+        for version in VERSIONS.iter() {
+            let mut ranges = vec![];
+
+            for id in RecordId::ALL.iter() {
+                let record = match get_record(*version, *id) {
+                    Some(record) => record,
+                    None => continue,
+                };
+
+                assert!(record.length > 0, "{:?} {:?}", version, id);
+                assert!(record.count > 0, "{:?} {:?}", version, id);
+                assert!(record.address & 0xFFFF >= 0x8000, "{:?} {:?}", version, id);
+
+                let start = mapper.address_to_offset(record.address).unwrap();
+                let end = start + record.length * record.count;
+                assert!(end <= 0x100000, "{:?} {:?}", version, id);
+
+                ranges.push((start, end, *id));
+            }
+
+            // Every entry of a table must lie before the start of the next table.
+            ranges.sort_by_key(|&(start, _, _)| start);
+            for pair in ranges.windows(2) {
+                let (_, end, id) = pair[0];
+                let (start, _, next) = pair[1];
+                assert!(end <= start, "{:?} {:?} runs into {:?}", version, id, next);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unmapped_versions() {
+        assert!(get_record(Version::UsRevA, RecordId::TextDialogue).is_some());
+
+        for version in [Version::Japan, Version::JapanRevA, Version::EasyType].iter() {
+            assert!(RecordId::ALL
+                .iter()
+                .all(|id| get_record(*version, *id).is_none()));
+        }
+    }
+}
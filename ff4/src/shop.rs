@@ -0,0 +1,155 @@
+use nom::{bytes::complete::take, error::ErrorKind, number::complete::le_u8, IResult};
+
+use crate::item;
+use crate::rom;
+use crate::rom_map::record::RecordId;
+use crate::Error;
+
+pub const ITEMS_PER_SHOP: usize = 8;
+
+const NO_ITEM: u8 = 0x00;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ShopKind {
+    Weapon,
+    Armor,
+    Item,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ShopItem {
+    pub id: u8,
+    pub name: String,
+    pub price: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Shop {
+    pub kind: ShopKind,
+    pub items: Vec<ShopItem>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ShopRecord {
+    kind: ShopKind,
+    items: Vec<u8>,
+}
+
+impl Shop {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let record = rom.parse_record(RecordId::Shop, index, parse_shop)?;
+
+        let items = record
+            .items
+            .iter()
+            .map(|&id| {
+                Ok(ShopItem {
+                    id,
+                    name: item::get_name(rom, id.into())?,
+                    price: item::get_price(rom, id.into())?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Shop {
+            kind: record.kind,
+            items,
+        })
+    }
+}
+
+pub(crate) fn parse_shop(input: &[u8]) -> IResult<&[u8], ShopRecord> {
+    let (input, kind) = le_u8(input)?;
+    let kind = match kind {
+        0 => ShopKind::Weapon,
+        1 => ShopKind::Armor,
+        2 => ShopKind::Item,
+        _ => return Err(nom::Err::Error((input, ErrorKind::MapOpt))),
+    };
+
+    let (input, items) = take(ITEMS_PER_SHOP)(input)?;
+    let items = items.iter().copied().filter(|&id| id != NO_ITEM).collect();
+
+    Ok((input, ShopRecord { kind, items }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_parse_shop() {
+        assert_eq!(
+            parse_shop(&[0x02, 0xCE, 0xCF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+            Ok((
+                &[][..],
+                ShopRecord {
+                    kind: ShopKind::Item,
+                    items: vec![0xCE, 0xCF],
+                }
+            ))
+        );
+        assert!(parse_shop(&[0x03, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+        assert!(parse_shop(&[0x00, 0x01, 0x02]).is_err());
+
+        // Empty slots are skipped wherever they appear.
+        assert_eq!(
+            parse_shop(&[0x00, 0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03])
+                .unwrap()
+                .1
+                .items,
+            [0x01, 0x02, 0x03]
+        );
+    }
+
+    #[test]
+    fn test_shop_new() {
+        let rom = RomBuilder::new(Version::Us)
+            .write(
+                RecordId::Shop,
+                4,
+                &[0x01, 0x62, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            )
+            .write(RecordId::ItemPrice, 0x62, &[0x0A])
+            .write(
+                RecordId::TextItemName,
+                0x62,
+                &[0x03, 0x47, 0x6D, 0x6E, 0x6F, 0xFF, 0xFF, 0xFF, 0xFF],
+            )
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            Shop::new(&rom, 4).unwrap(),
+            Shop {
+                kind: ShopKind::Armor,
+                items: vec![ShopItem {
+                    id: 0x62,
+                    name: String::from("{icon:3}Frst"),
+                    price: 100,
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_shop_new_invalid_kind() {
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::Shop, 2, &[0x04, 0, 0, 0, 0, 0, 0, 0, 0])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            Shop::new(&rom, 2),
+            Err(Error::Parse {
+                address: 0x0FC812,
+                ..
+            })
+        ));
+        assert!(Shop::new(&rom, 3).unwrap().items.is_empty());
+    }
+}
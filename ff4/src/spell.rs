@@ -0,0 +1,325 @@
+use nom::{
+    bytes::complete::take_till,
+    number::complete::{le_u16, le_u8},
+    IResult,
+};
+
+use crate::item;
+use crate::rom;
+use crate::rom_map::record::RecordId;
+use crate::text;
+use crate::Error;
+
+pub const FIRST_SUMMON: usize = 0x31;
+pub const SUMMON_COUNT: usize = 0x10;
+
+const CASTING_DELAY: u8 = 0x1F;
+const TARGET_MODE_SHIFT: u8 = 5;
+const HIT_RATE: u8 = 0x7F;
+const MP_COST: u8 = 0x7F;
+const NOT_REFLECTABLE: u8 = 0x80;
+
+const LIST_END: u8 = 0xFF;
+const NO_SPELL_SET: u8 = 0xFF;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TargetMode {
+    Caster,
+    SingleAlly,
+    AllAllies,
+    SingleEnemy,
+    AllEnemies,
+    SingleOrAllAllies,
+    SingleOrAllEnemies,
+    Random,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Spell {
+    pub name: String,
+    pub casting_delay: u8,
+    pub target: TargetMode,
+    pub power: u8,
+    pub hit_rate: u8,
+    pub reflectable: bool,
+    pub mp_cost: u8,
+    pub element: u8,
+    pub status: u16,
+    pub effect: u8,
+    pub animation: u8,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct SpellRecord {
+    casting_delay: u8,
+    target: TargetMode,
+    power: u8,
+    hit_rate: u8,
+    attributes: u8,
+    effect: u8,
+    mp_cost: u8,
+    reflectable: bool,
+}
+
+/// A list of spells known on joining the party and the levels at which the rest are learned.
+#[derive(Debug, Default, PartialEq)]
+pub struct SpellSet {
+    pub initial: Vec<u8>,
+    pub learned: Vec<(u8, u8)>,
+}
+
+impl Spell {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let record = rom.parse_record(RecordId::Spell, index, parse_spell)?;
+        let attributes = item::Attributes::new(rom, record.attributes.into())?;
+
+        Ok(Spell {
+            name: get_name(rom, index)?,
+            casting_delay: record.casting_delay,
+            target: record.target,
+            power: record.power,
+            hit_rate: record.hit_rate,
+            reflectable: record.reflectable,
+            mp_cost: record.mp_cost,
+            element: attributes.element,
+            status: attributes.status,
+            effect: record.effect,
+            animation: rom.read_bytes(RecordId::SpellAnimation, index)?[0],
+        })
+    }
+}
+
+impl SpellSet {
+    pub fn new(rom: &rom::Rom, index: usize) -> Result<Self, Error> {
+        let record = rom.record(RecordId::SpellSetData)?;
+        let offset =
+            usize::from(rom.parse_record(RecordId::SpellSetPointer, index, parse_pointer)?);
+        let data = rom.read_bytes(RecordId::SpellSetData, 0)?;
+
+        data.get(offset..)
+            .and_then(|input| parse_spell_set(input).ok())
+            .map(|(_, set)| set)
+            .ok_or(Error::Parse {
+                record: record.name,
+                address: record.address + offset,
+            })
+    }
+}
+
+/// Summons have their own name table; every other spell is named from the spell name table.
+pub fn get_name(rom: &rom::Rom, index: usize) -> Result<String, Error> {
+    if (FIRST_SUMMON..FIRST_SUMMON + SUMMON_COUNT).contains(&index) {
+        text::read_name(rom, RecordId::TextSummonName, index - FIRST_SUMMON)
+    } else {
+        text::read_name(rom, RecordId::TextSpellName, index)
+    }
+}
+
+/// Returns the indexes of the spell sets available to a character.
+pub fn get_character_spell_sets(rom: &rom::Rom, character: usize) -> Result<Vec<usize>, Error> {
+    Ok(rom
+        .read_bytes(RecordId::CharacterSpellSets, character)?
+        .iter()
+        .filter(|&&set| set != NO_SPELL_SET)
+        .map(|&set| usize::from(set))
+        .collect())
+}
+
+pub(crate) fn parse_spell(input: &[u8]) -> IResult<&[u8], SpellRecord> {
+    let (input, delay_target) = le_u8(input)?;
+    let casting_delay = delay_target & CASTING_DELAY;
+    let target = match delay_target >> TARGET_MODE_SHIFT {
+        0 => TargetMode::Caster,
+        1 => TargetMode::SingleAlly,
+        2 => TargetMode::AllAllies,
+        3 => TargetMode::SingleEnemy,
+        4 => TargetMode::AllEnemies,
+        5 => TargetMode::SingleOrAllAllies,
+        6 => TargetMode::SingleOrAllEnemies,
+        _ => TargetMode::Random,
+    };
+
+    let (input, power) = le_u8(input)?;
+    let (input, hit_rate) = le_u8(input)?;
+    let (input, attributes) = le_u8(input)?;
+    let (input, effect) = le_u8(input)?;
+    let (input, mp_cost_flags) = le_u8(input)?;
+
+    Ok((
+        input,
+        SpellRecord {
+            casting_delay,
+            target,
+            power,
+            hit_rate: hit_rate & HIT_RATE,
+            attributes,
+            effect,
+            mp_cost: mp_cost_flags & MP_COST,
+            reflectable: mp_cost_flags & NOT_REFLECTABLE == 0,
+        },
+    ))
+}
+
+pub fn parse_spell_set(input: &[u8]) -> IResult<&[u8], SpellSet> {
+    let (input, initial) = take_till(|byte| byte == LIST_END)(input)?;
+    let (mut input, _) = le_u8(input)?;
+
+    let mut learned = Vec::new();
+
+    loop {
+        let (rest, level) = le_u8(input)?;
+
+        if level == LIST_END {
+            input = rest;
+            break;
+        }
+
+        let (rest, spell) = le_u8(rest)?;
+        learned.push((level, spell));
+        input = rest;
+    }
+
+    Ok((
+        input,
+        SpellSet {
+            initial: initial.to_vec(),
+            learned,
+        },
+    ))
+}
+
+fn parse_pointer(input: &[u8]) -> IResult<&[u8], u16> {
+    le_u16(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_parse_spell() {
+        assert_eq!(
+            parse_spell(&[0xA3, 0x14, 0xE4, 0x05, 0x2A, 0x89]),
+            Ok((
+                &[][..],
+                SpellRecord {
+                    casting_delay: 3,
+                    target: TargetMode::SingleOrAllAllies,
+                    power: 20,
+                    hit_rate: 100,
+                    attributes: 5,
+                    effect: 0x2A,
+                    mp_cost: 9,
+                    reflectable: false,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_spell_set() {
+        assert_eq!(
+            parse_spell_set(&[0x01, 0x02, 0xFF, 0x05, 0x03, 0x0A, 0x04, 0xFF, 0x99]),
+            Ok((
+                &[0x99][..],
+                SpellSet {
+                    initial: vec![1, 2],
+                    learned: vec![(5, 3), (10, 4)],
+                }
+            ))
+        );
+
+        assert!(parse_spell_set(&[0x01, 0x02]).is_err());
+        assert!(parse_spell_set(&[0x01, 0xFF, 0x05, 0x03]).is_err());
+    }
+
+    #[test]
+    fn test_spell_new() {
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::Spell, 2, &[0x60, 0x10, 0x50, 0x01, 0x01, 0x05])
+            .write(RecordId::SpellAnimation, 2, &[0x1D])
+            .write(RecordId::ItemAttributes, 1, &[0x04, 0x00, 0x00, 0x80])
+            .write(
+                RecordId::TextSpellName,
+                2,
+                &[0x01, 0x45, 0x64, 0x75, 0x60, 0xFF],
+            )
+            .write(
+                RecordId::TextSummonName,
+                1,
+                &[0x48, 0x64, 0x69, 0x5C, 0xFF, 0xFF, 0xFF, 0xFF],
+            )
+            .write(RecordId::SpellSetPointer, 1, &[0x04, 0x00])
+            .write(
+                RecordId::SpellSetData,
+                0,
+                &[0xFF, 0xFF, 0xFF, 0xFF, 0x0C, 0xFF, 0xFF],
+            )
+            .write(RecordId::CharacterSpellSets, 3, &[0x01, 0x02, 0xFF])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let spell = Spell::new(&rom, 2).unwrap();
+        assert_eq!(spell.name, "{icon:1}Dize");
+        assert_eq!(spell.target, TargetMode::SingleEnemy);
+        assert_eq!(spell.element, 0x04);
+        assert_eq!(spell.status, 0x8000);
+        assert_eq!(spell.mp_cost, 5);
+        assert_eq!(spell.animation, 0x1D);
+        assert!(spell.reflectable);
+
+        assert_eq!(get_name(&rom, FIRST_SUMMON + 1).unwrap(), "Gina");
+        assert_eq!(get_character_spell_sets(&rom, 3).unwrap(), vec![1, 2]);
+        assert_eq!(
+            SpellSet::new(&rom, 1).unwrap(),
+            SpellSet {
+                initial: vec![0x0C],
+                learned: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_spell_new_out_of_range() {
+        let rom = RomBuilder::new(Version::Us)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            Spell::new(&rom, 0x80),
+            Err(Error::IndexOutOfRange {
+                record: "spell",
+                index: 0x80,
+            })
+        ));
+        assert!(get_name(&rom, 0x47).is_ok());
+        assert!(matches!(
+            get_name(&rom, 0x48),
+            Err(Error::IndexOutOfRange { index: 0x48, .. })
+        ));
+    }
+
+    #[test]
+    fn test_spell_set_new_invalid() {
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::SpellSetPointer, 0, &[0xAF, 0x01])
+            .write(RecordId::SpellSetPointer, 1, &[0x00, 0x02])
+            .write(RecordId::SpellSetData, 0, &[0x00; 0x1B0])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            SpellSet::new(&rom, 0),
+            Err(Error::Parse {
+                address: 0x0F8FFF,
+                ..
+            })
+        ));
+        assert!(matches!(SpellSet::new(&rom, 1), Err(Error::Parse { .. })));
+    }
+}
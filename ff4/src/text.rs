@@ -0,0 +1,492 @@
+use std::fmt;
+
+use crate::rom;
+use crate::rom_map::record::RecordId;
+use crate::Error;
+
+const CODE_END: u8 = 0x00;
+const CODE_LINE_BREAK: u8 = 0x01;
+const CODE_CHARACTER_NAME: u8 = 0x02;
+const CODE_PAUSE: u8 = 0x03;
+const CODE_ITEM_NAME: u8 = 0x04;
+const CODE_NUMBER: u8 = 0x05;
+
+const FIRST_CHARACTER: u8 = 0x42;
+const FIRST_UPPERCASE: u8 = 0x42;
+const FIRST_LOWERCASE: u8 = 0x5C;
+const FIRST_DIGIT: u8 = 0x80;
+const FIRST_PUNCTUATION: u8 = 0xC0;
+const FIRST_DTE: u8 = 0x80;
+const SPACE: u8 = 0xFF;
+
+const PUNCTUATION: [char; 10] = ['\'', '.', '-', '…', '!', '?', '%', '/', ':', ','];
+
+const MAXIMUM_DIALOGUE_LENGTH: usize = 0x400;
+
+/// A single unit of decoded text.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Token {
+    Char(char),
+    LineBreak,
+    Pause(u8),
+    CharacterName(u8),
+    ItemName,
+    Number,
+    Icon(u8),
+    Unknown(u8),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Char(c) => write!(f, "{}", c),
+            Token::LineBreak => writeln!(f),
+            Token::Pause(length) => write!(f, "{{pause:{}}}", length),
+            Token::CharacterName(index) => write!(f, "{{name:{}}}", index),
+            Token::ItemName => write!(f, "{{item}}"),
+            Token::Number => write!(f, "{{number}}"),
+            Token::Icon(index) => write!(f, "{{icon:{}}}", index),
+            Token::Unknown(byte) => write!(f, "{{${:02X}}}", byte),
+        }
+    }
+}
+
+/// The dual-tile encoding table used to compress dialogue. Any dialogue byte from $80 upwards
+/// that is not a single character expands to the pair stored at `byte - $80`.
+pub struct Dictionary {
+    pairs: Vec<[u8; 2]>,
+}
+
+impl Dictionary {
+    pub fn new(rom: &rom::Rom) -> Result<Self, Error> {
+        let pairs = (0..usize::from(SPACE - FIRST_DTE))
+            .map(|index| {
+                let bytes = rom.read_bytes(RecordId::TextDteTable, index)?;
+                Ok([bytes[0], bytes[1]])
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Dictionary { pairs })
+    }
+
+    pub fn from_pairs(pairs: Vec<[u8; 2]>) -> Self {
+        Dictionary { pairs }
+    }
+
+    pub fn pair(&self, byte: u8) -> Option<[u8; 2]> {
+        if byte < FIRST_DTE || decode_char(byte).is_some() {
+            return None;
+        }
+
+        self.pairs.get(usize::from(byte - FIRST_DTE)).copied()
+    }
+
+    pub fn code(&self, pair: [u8; 2]) -> Option<u8> {
+        (FIRST_DTE..SPACE)
+            .filter(|&byte| decode_char(byte).is_none())
+            .find(|&byte| self.pair(byte) == Some(pair))
+    }
+}
+
+pub fn decode_char(byte: u8) -> Option<char> {
+    match byte {
+        FIRST_UPPERCASE..=0x5B => Some(char::from(b'A' + (byte - FIRST_UPPERCASE))),
+        FIRST_LOWERCASE..=0x75 => Some(char::from(b'a' + (byte - FIRST_LOWERCASE))),
+        FIRST_DIGIT..=0x89 => Some(char::from(b'0' + (byte - FIRST_DIGIT))),
+        FIRST_PUNCTUATION..=0xC9 => Some(PUNCTUATION[usize::from(byte - FIRST_PUNCTUATION)]),
+        SPACE => Some(' '),
+        _ => None,
+    }
+}
+
+pub fn encode_char(c: char) -> Option<u8> {
+    match c {
+        'A'..='Z' => Some(FIRST_UPPERCASE + (c as u8 - b'A')),
+        'a'..='z' => Some(FIRST_LOWERCASE + (c as u8 - b'a')),
+        '0'..='9' => Some(FIRST_DIGIT + (c as u8 - b'0')),
+        ' ' => Some(SPACE),
+        _ => PUNCTUATION
+            .iter()
+            .position(|&p| p == c)
+            .map(|position| FIRST_PUNCTUATION + position as u8),
+    }
+}
+
+/// Decodes dialogue up to its terminating byte, expanding dual-tile pairs and control codes.
+pub fn decode_dialogue(bytes: &[u8], dictionary: &Dictionary) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut iter = bytes.iter().copied();
+
+    while let Some(byte) = iter.next() {
+        match byte {
+            CODE_END => break,
+            CODE_LINE_BREAK => tokens.push(Token::LineBreak),
+            CODE_CHARACTER_NAME => tokens.push(Token::CharacterName(iter.next().unwrap_or(0))),
+            CODE_PAUSE => tokens.push(Token::Pause(iter.next().unwrap_or(0))),
+            CODE_ITEM_NAME => tokens.push(Token::ItemName),
+            CODE_NUMBER => tokens.push(Token::Number),
+            _ => match (decode_char(byte), dictionary.pair(byte)) {
+                (Some(c), _) => tokens.push(Token::Char(c)),
+                (None, Some(pair)) => tokens.extend(pair.iter().map(|&b| decode_name_byte(b))),
+                (None, None) => tokens.push(Token::Unknown(byte)),
+            },
+        }
+    }
+
+    tokens
+}
+
+/// Decodes an entry from a fixed-width name table, dropping the trailing padding.
+pub fn decode_name(bytes: &[u8]) -> Vec<Token> {
+    let length = bytes
+        .iter()
+        .rposition(|&byte| byte != SPACE)
+        .map_or(0, |position| position + 1);
+
+    bytes[..length]
+        .iter()
+        .map(|&b| decode_name_byte(b))
+        .collect()
+}
+
+fn decode_name_byte(byte: u8) -> Token {
+    match decode_char(byte) {
+        Some(c) => Token::Char(c),
+        None if byte < FIRST_CHARACTER => Token::Icon(byte),
+        None => Token::Unknown(byte),
+    }
+}
+
+pub fn to_string(tokens: &[Token]) -> String {
+    tokens.iter().map(|token| token.to_string()).collect()
+}
+
+/// Parses text in the form produced by `to_string` back into tokens.
+pub fn parse(text: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\n' => Token::LineBreak,
+            '{' => {
+                let tag: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                parse_tag(&tag).ok_or_else(|| Error::InvalidMarkup(format!("{{{}}}", tag)))?
+            }
+            _ => Token::Char(c),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn parse_tag(tag: &str) -> Option<Token> {
+    if let Some(hex) = tag.strip_prefix('$') {
+        return u8::from_str_radix(hex, 16).ok().map(Token::Unknown);
+    }
+
+    let mut parts = tag.splitn(2, ':');
+    let name = parts.next()?;
+    let argument = parts.next().map(|argument| argument.parse::<u8>());
+
+    match (name, argument) {
+        ("item", None) => Some(Token::ItemName),
+        ("number", None) => Some(Token::Number),
+        ("pause", Some(Ok(length))) => Some(Token::Pause(length)),
+        ("name", Some(Ok(index))) => Some(Token::CharacterName(index)),
+        ("icon", Some(Ok(index))) if index < FIRST_CHARACTER => Some(Token::Icon(index)),
+        _ => None,
+    }
+}
+
+/// Encodes dialogue, including its terminating byte. Adjacent characters are replaced with a
+/// dual-tile code wherever the dictionary has one; taking the leftmost pair each time gives the
+/// shortest encoding.
+pub fn encode_dialogue(text: &str, dictionary: &Dictionary) -> Result<Vec<u8>, Error> {
+    let tokens = parse(text)?;
+    check_representable(&tokens)?;
+
+    let mut bytes = Vec::new();
+    let mut iter = tokens.iter().peekable();
+
+    while let Some(token) = iter.next() {
+        match token {
+            Token::Char(c) => {
+                let first = encode_char(*c).unwrap();
+                let code = match iter.peek() {
+                    Some(Token::Char(next)) => {
+                        dictionary.code([first, encode_char(*next).unwrap()])
+                    }
+                    _ => None,
+                };
+
+                match code {
+                    Some(code) => {
+                        iter.next();
+                        bytes.push(code);
+                    }
+                    None => bytes.push(first),
+                }
+            }
+            Token::LineBreak => bytes.push(CODE_LINE_BREAK),
+            Token::Pause(length) => bytes.extend_from_slice(&[CODE_PAUSE, *length]),
+            Token::CharacterName(index) => bytes.extend_from_slice(&[CODE_CHARACTER_NAME, *index]),
+            Token::ItemName => bytes.push(CODE_ITEM_NAME),
+            Token::Number => bytes.push(CODE_NUMBER),
+            Token::Icon(byte) | Token::Unknown(byte) => bytes.push(*byte),
+        }
+    }
+
+    bytes.push(CODE_END);
+
+    Ok(bytes)
+}
+
+/// Encodes an entry for a fixed-width name table, padding it to `width` bytes.
+pub fn encode_name(text: &str, width: usize) -> Result<Vec<u8>, Error> {
+    let tokens = parse(text)?;
+    check_representable(&tokens)?;
+
+    let mut bytes = tokens
+        .iter()
+        .map(|token| match token {
+            Token::Char(c) => Ok(encode_char(*c).unwrap()),
+            Token::Icon(byte) | Token::Unknown(byte) => Ok(*byte),
+            _ => Err(Error::InvalidMarkup(token.to_string())),
+        })
+        .collect::<Result<Vec<u8>, Error>>()?;
+
+    if bytes.len() > width {
+        return Err(Error::TextTooLong {
+            length: bytes.len(),
+            maximum: width,
+        });
+    }
+
+    bytes.resize(width, SPACE);
+
+    Ok(bytes)
+}
+
+fn check_representable(tokens: &[Token]) -> Result<(), Error> {
+    let mut unrepresentable = Vec::new();
+
+    for token in tokens {
+        if let Token::Char(c) = token {
+            if encode_char(*c).is_none() && !unrepresentable.contains(c) {
+                unrepresentable.push(*c);
+            }
+        }
+    }
+
+    if unrepresentable.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Unrepresentable(unrepresentable))
+    }
+}
+
+pub fn read_dialogue(
+    rom: &rom::Rom,
+    dictionary: &Dictionary,
+    index: usize,
+) -> Result<Vec<Token>, Error> {
+    let pointer = rom.read_bytes(RecordId::TextDialoguePointer, index)?;
+    let offset = usize::from(pointer[0]) | (usize::from(pointer[1]) << 8);
+
+    let record = rom.record(RecordId::TextDialogue)?;
+    let data = rom.read_bytes(RecordId::TextDialogue, 0)?;
+    let error = || Error::Parse {
+        record: record.name,
+        address: record.address + offset,
+    };
+
+    let mut bytes = Vec::new();
+
+    loop {
+        let byte = data.get(offset + bytes.len()).copied().ok_or_else(error)?;
+
+        // Parameters of control codes may be zero, so they must not end the string.
+        if byte == CODE_END && !ends_with_control_code(&bytes) {
+            break;
+        }
+
+        bytes.push(byte);
+
+        if bytes.len() > MAXIMUM_DIALOGUE_LENGTH {
+            return Err(error());
+        }
+    }
+
+    Ok(decode_dialogue(&bytes, dictionary))
+}
+
+fn ends_with_control_code(bytes: &[u8]) -> bool {
+    let mut iter = bytes.iter();
+    let mut pending = false;
+
+    while let Some(&byte) = iter.next() {
+        pending = matches!(byte, CODE_CHARACTER_NAME | CODE_PAUSE);
+
+        if pending && iter.next().is_some() {
+            pending = false;
+        }
+    }
+
+    pending
+}
+
+pub fn read_name(rom: &rom::Rom, id: RecordId, index: usize) -> Result<String, Error> {
+    Ok(to_string(&decode_name(rom.read_bytes(id, index)?)))
+}
+
+pub fn write_name(rom: &mut rom::Rom, id: RecordId, index: usize, text: &str) -> Result<(), Error> {
+    let bytes = encode_name(text, rom.record(id)?.length)?;
+    rom.write_bytes(id, index, &bytes)
+}
+
+pub fn read_character_name(rom: &rom::Rom, index: usize) -> Result<String, Error> {
+    read_name(rom, RecordId::TextCharacterName, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    fn dictionary() -> Dictionary {
+        let mut pairs = vec![[SPACE, SPACE]; 0x7F];
+        pairs[0x10] = [0x6F, 0x63]; // "th"
+        pairs[0x11] = [0x60, SPACE]; // "e "
+        Dictionary::from_pairs(pairs)
+    }
+
+    #[test]
+    fn test_decode_char() {
+        assert_eq!(decode_char(0x42), Some('A'));
+        assert_eq!(decode_char(0x75), Some('z'));
+        assert_eq!(decode_char(0x89), Some('9'));
+        assert_eq!(decode_char(0xC5), Some('?'));
+        assert_eq!(decode_char(0xFF), Some(' '));
+        assert_eq!(decode_char(0x90), None);
+    }
+
+    #[test]
+    fn test_decode_dialogue() {
+        let tokens = decode_dialogue(
+            &[
+                0x90, 0x91, 0x01, 0x02, 0x00, 0xC4, 0x03, 0x10, 0x04, 0x05, 0x06, 0x00, 0x42,
+            ],
+            &dictionary(),
+        );
+
+        assert_eq!(
+            to_string(&tokens),
+            "the \n{name:0}!{pause:16}{item}{number}{$06}"
+        );
+    }
+
+    #[test]
+    fn test_decode_name() {
+        let tokens = decode_name(&[0x05, 0x4C, 0x64, 0x69, 0xFF, 0xFF]);
+
+        assert_eq!(tokens[0], Token::Icon(5));
+        assert_eq!(to_string(&tokens), "{icon:5}Kin");
+    }
+
+    #[test]
+    fn test_read_dialogue() {
+        let mut dialogue = vec![0; 0xBC00];
+        dialogue[0x20..0x25].copy_from_slice(&[0x90, 0x60, 0x02, 0x00, 0x00]);
+        dialogue[0x7BFE..0x7C01].copy_from_slice(&[0x42, 0x43, 0x00]);
+        dialogue[0xBBFF] = 0x42;
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::TextDteTable, 0x10, &[0x6F, 0x63])
+            .write(RecordId::TextDialoguePointer, 2, &[0x20, 0x00])
+            .write(RecordId::TextDialoguePointer, 3, &[0xFE, 0x7B])
+            .write(RecordId::TextDialoguePointer, 4, &[0xFF, 0xBB])
+            .write(RecordId::TextDialogue, 0, &dialogue)
+            .write(
+                RecordId::TextCharacterName,
+                1,
+                &[0x4C, 0x60, 0x69, 0xFF, 0xFF, 0xFF],
+            )
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let dictionary = Dictionary::new(&rom).unwrap();
+
+        assert_eq!(
+            to_string(&read_dialogue(&rom, &dictionary, 2).unwrap()),
+            "the{name:0}"
+        );
+        assert_eq!(
+            to_string(&read_dialogue(&rom, &dictionary, 3).unwrap()),
+            "AB"
+        );
+        assert!(matches!(
+            read_dialogue(&rom, &dictionary, 4),
+            Err(Error::Parse { .. })
+        ));
+        assert_eq!(read_character_name(&rom, 1).unwrap(), "Ken");
+    }
+
+    #[test]
+    fn test_encode_dialogue() {
+        let text = "the \n{name:0}!{pause:16}{item}{number}{$06}";
+        let bytes = encode_dialogue(text, &dictionary()).unwrap();
+
+        assert_eq!(
+            bytes,
+            [0x90, 0x91, 0x01, 0x02, 0x00, 0xC4, 0x03, 0x10, 0x04, 0x05, 0x06, 0x00]
+        );
+        assert_eq!(to_string(&decode_dialogue(&bytes, &dictionary())), text);
+
+        // The leftmost pair wins, so "ethe" only compresses its middle "th".
+        assert_eq!(
+            encode_dialogue("ethe", &dictionary()).unwrap(),
+            [0x60, 0x90, 0x60, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        assert!(matches!(
+            encode_dialogue("Café ☃é", &dictionary()),
+            Err(Error::Unrepresentable(ref chars)) if chars == &['é', '☃']
+        ));
+        assert!(matches!(
+            encode_dialogue("{bogus}", &dictionary()),
+            Err(Error::InvalidMarkup(_))
+        ));
+        assert!(matches!(
+            encode_name("Tellah1", 6),
+            Err(Error::TextTooLong {
+                length: 7,
+                maximum: 6
+            })
+        ));
+    }
+
+    #[test]
+    fn test_encode_name() {
+        assert_eq!(
+            encode_name("{icon:5}Kin", 6).unwrap(),
+            [0x05, 0x4C, 0x64, 0x69, 0xFF, 0xFF]
+        );
+        assert!(encode_name("{pause:1}", 6).is_err());
+
+        let mut rom = RomBuilder::new(Version::Us)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        write_name(&mut rom, RecordId::TextCharacterName, 2, "Rosa").unwrap();
+        assert_eq!(read_character_name(&rom, 2).unwrap(), "Rosa");
+    }
+}
@@ -2,7 +2,8 @@ use image::Rgba;
 
 use crate::graphics;
 use crate::rom;
-use crate::rom_map::record;
+use crate::rom_map::record::RecordId;
+use crate::Error;
 
 const TILEMAP_FLAGS_HIGH_VALUE: u8 = 0x03;
 const TILEMAP_FLAGS_PALETTE: u8 = 0x1C;
@@ -27,11 +28,11 @@ pub struct Title {
 }
 
 impl Title {
-    pub fn new(rom: &rom::Rom) -> Self {
-        let tilemap = parse_tilemap(rom.read_bytes(record::TITLE_TILEMAP, 0));
-        let palette = rom.read_palette(record::TITLE_PALETTE, 0, 1);
+    pub fn new(rom: &rom::Rom) -> Result<Self, Error> {
+        let tilemap = parse_tilemap(rom.read_bytes(RecordId::TitleTilemap, 0)?);
+        let palette = rom.read_palette(RecordId::TitlePalette, 0, 1)?;
 
-        let tile_data = rom.read_bytes(record::TITLE_TILES, 0);
+        let tile_data = rom.read_bytes(RecordId::TitleTiles, 0)?;
         let tile_count = tile_data.len() / graphics::BYTES_PER_TILE_4BPP;
 
         let tiles = (0..tile_count)
@@ -43,13 +44,13 @@ impl Title {
             })
             .collect();
 
-        Title {
+        Ok(Title {
             palette,
             tiles,
             tilemap,
             width: 256,
             height: 256,
-        }
+        })
     }
 }
 
@@ -75,3 +76,44 @@ fn parse_tilemap(data: &[u8]) -> Vec<TilemapEntry> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_title_new() {
+        let mut tile = [0; graphics::BYTES_PER_TILE_4BPP];
+        tile[0] = 0xFF;
+        tile[17] = 0x01;
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::TitleTilemap, 0, &[0x05, 0xE5, 0x01, 0x00])
+            .write(RecordId::TitleTiles, 0, &[[0; 32], tile].concat())
+            .write(RecordId::TitlePalette, 0, &[0xFF, 0x7F, 0x1F, 0x00])
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let title = Title::new(&rom).unwrap();
+
+        assert_eq!(title.tilemap.len(), 1024);
+        assert_eq!(title.tilemap[0].value, 0x105);
+        assert_eq!(title.tilemap[0].palette, 1);
+        assert!(title.tilemap[0].priority);
+        assert!(title.tilemap[0].vflip);
+        assert!(title.tilemap[0].hflip);
+        assert_eq!(title.tilemap[1].value, 0x001);
+        assert!(!title.tilemap[1].hflip);
+
+        assert_eq!(title.tiles.len(), 256);
+        assert_eq!(title.tiles[1][0..8], [1, 1, 1, 1, 1, 1, 1, 9]);
+        assert_eq!(title.tiles[1][8], 0);
+
+        assert_eq!(title.palette.len(), 256);
+        assert_eq!(title.palette[0], Rgba([255, 255, 255, 255]));
+        assert_eq!(title.palette[1], Rgba([255, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,270 @@
+use nom::{
+    multi::count,
+    number::complete::{le_u16, le_u8},
+    IResult,
+};
+
+use crate::item;
+use crate::map::{Facing, OutdoorMap};
+use crate::rom;
+use crate::rom_map::record::RecordId;
+use crate::Error;
+
+const KIND_TREASURE: u8 = 0xFE;
+const KIND_EVENT: u8 = 0xFF;
+
+const LOCATION_BANK: usize = 0x100;
+
+const TELEPORT_X: u8 = 0x3F;
+const TELEPORT_FACING_SHIFT: u8 = 6;
+
+const TREASURE_GIL: u8 = 0x80;
+const TREASURE_TRAPPED: u8 = 0x40;
+const TREASURE_FORMATION: u8 = 0x3F;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TreasureContents {
+    Item(u8),
+    Gil(u32),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Treasure {
+    pub contents: TreasureContents,
+    pub trap: Option<u8>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TriggerAction {
+    /// The trigger stores only the low byte of the destination; the bank comes from the map the
+    /// trigger is on.
+    Teleport {
+        location: usize,
+        x: u8,
+        y: u8,
+        facing: Facing,
+    },
+    Event(u8),
+    Treasure(Treasure),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Trigger {
+    pub x: u8,
+    pub y: u8,
+    pub action: TriggerAction,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TriggerTable {
+    pub triggers: Vec<Trigger>,
+}
+
+impl TriggerTable {
+    pub fn new_outdoor(rom: &rom::Rom, map: OutdoorMap) -> Result<Self, Error> {
+        // The overworld leads to the surface locations; the underworld and moon to the second bank.
+        let bank = match map {
+            OutdoorMap::Overworld => 0,
+            OutdoorMap::Underworld | OutdoorMap::Moon => LOCATION_BANK,
+        };
+
+        read_trigger_table(rom, RecordId::TriggerOutdoorPointer, map as usize, bank)
+    }
+
+    pub fn new_location(rom: &rom::Rom, location: usize) -> Result<Self, Error> {
+        read_trigger_table(
+            rom,
+            RecordId::TriggerLocationPointer,
+            location,
+            location & LOCATION_BANK,
+        )
+    }
+
+    /// Returns the trigger on a tile, if there is one.
+    pub fn get(&self, x: u8, y: u8) -> Option<&Trigger> {
+        self.triggers
+            .iter()
+            .find(|trigger| trigger.x == x && trigger.y == y)
+    }
+}
+
+fn read_trigger_table(
+    rom: &rom::Rom,
+    pointer_id: RecordId,
+    index: usize,
+    bank: usize,
+) -> Result<TriggerTable, Error> {
+    let record = rom.record(RecordId::TriggerData)?;
+    let offset = usize::from(rom.parse_record(pointer_id, index, parse_pointer)?);
+    let data = rom.read_bytes(RecordId::TriggerData, 0)?;
+
+    data.get(offset..)
+        .and_then(|input| parse_trigger_table(input, bank).ok())
+        .map(|(_, table)| table)
+        .ok_or(Error::Parse {
+            record: record.name,
+            address: record.address + offset,
+        })
+}
+
+pub fn parse_trigger_table(input: &[u8], bank: usize) -> IResult<&[u8], TriggerTable> {
+    let (input, length) = le_u8(input)?;
+    let (input, triggers) = count(|input| parse_trigger(input, bank), length.into())(input)?;
+
+    Ok((input, TriggerTable { triggers }))
+}
+
+/// Parses a trigger on a map whose teleports lead to locations in `bank`.
+pub fn parse_trigger(input: &[u8], bank: usize) -> IResult<&[u8], Trigger> {
+    let (input, x) = le_u8(input)?;
+    let (input, y) = le_u8(input)?;
+    let (input, kind) = le_u8(input)?;
+    let (input, argument_1) = le_u8(input)?;
+    let (input, argument_2) = le_u8(input)?;
+
+    let action = match kind {
+        KIND_EVENT => TriggerAction::Event(argument_1),
+        KIND_TREASURE => {
+            let contents = if argument_1 & TREASURE_GIL > 0 {
+                TreasureContents::Gil(item::decode_price(argument_2))
+            } else {
+                TreasureContents::Item(argument_2)
+            };
+
+            let trap = if argument_1 & TREASURE_TRAPPED > 0 {
+                Some(argument_1 & TREASURE_FORMATION)
+            } else {
+                None
+            };
+
+            TriggerAction::Treasure(Treasure { contents, trap })
+        }
+        location => TriggerAction::Teleport {
+            location: bank | usize::from(location),
+            x: argument_1 & TELEPORT_X,
+            y: argument_2,
+            facing: Facing::new(argument_1 >> TELEPORT_FACING_SHIFT),
+        },
+    };
+
+    Ok((input, Trigger { x, y, action }))
+}
+
+fn parse_pointer(input: &[u8]) -> IResult<&[u8], u16> {
+    le_u16(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::RomBuilder;
+    use crate::rom_map::Version;
+
+    #[test]
+    fn test_parse_trigger() {
+        assert_eq!(
+            parse_trigger(&[0x10, 0x20, 0x05, 0x8A, 0x0C], LOCATION_BANK),
+            Ok((
+                &[][..],
+                Trigger {
+                    x: 0x10,
+                    y: 0x20,
+                    action: TriggerAction::Teleport {
+                        location: 0x105,
+                        x: 10,
+                        y: 12,
+                        facing: Facing::Down,
+                    },
+                }
+            ))
+        );
+        assert_eq!(
+            parse_trigger(&[0x01, 0x02, 0xFF, 0x30, 0x00], 0)
+                .unwrap()
+                .1
+                .action,
+            TriggerAction::Event(0x30)
+        );
+        assert_eq!(
+            parse_trigger(&[0x01, 0x02, 0xFE, 0xC3, 0x85], 0)
+                .unwrap()
+                .1
+                .action,
+            TriggerAction::Treasure(Treasure {
+                contents: TreasureContents::Gil(5000),
+                trap: Some(3),
+            })
+        );
+        assert_eq!(
+            parse_trigger(&[0x01, 0x02, 0xFE, 0x00, 0xCE], 0)
+                .unwrap()
+                .1
+                .action,
+            TriggerAction::Treasure(Treasure {
+                contents: TreasureContents::Item(0xCE),
+                trap: None,
+            })
+        );
+        assert!(matches!(
+            parse_trigger(&[0x01, 0x02, 0xFD, 0x00, 0x00], LOCATION_BANK)
+                .unwrap()
+                .1
+                .action,
+            TriggerAction::Teleport {
+                location: 0x1FD,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_trigger_table_new() {
+        let mut data = vec![0; 0x10];
+        data.extend_from_slice(&[
+            0x02, 0x03, 0x04, 0xFF, 0x01, 0x00, 0x05, 0x06, 0x07, 0x08, 0x09,
+        ]);
+
+        let rom = RomBuilder::new(Version::Us)
+            .write(RecordId::TriggerOutdoorPointer, 0, &[0x10, 0x00])
+            .write(RecordId::TriggerOutdoorPointer, 2, &[0x10, 0x00])
+            .write(RecordId::TriggerLocationPointer, 7, &[0x20, 0x00])
+            .write(RecordId::TriggerLocationPointer, 0x107, &[0x10, 0x00])
+            .write(RecordId::TriggerData, 0, &data)
+            .skip_hash_check()
+            .build()
+            .unwrap();
+
+        let table = TriggerTable::new_outdoor(&rom, OutdoorMap::Moon).unwrap();
+
+        assert_eq!(table.triggers.len(), 2);
+        assert_eq!(table.get(3, 4).unwrap().action, TriggerAction::Event(1));
+        assert_eq!(
+            table.get(5, 6).unwrap().action,
+            TriggerAction::Teleport {
+                location: 0x107,
+                x: 8,
+                y: 9,
+                facing: Facing::Up,
+            }
+        );
+        assert!(table.get(4, 3).is_none());
+
+        let teleport = |table: TriggerTable| match table.get(5, 6).unwrap().action {
+            TriggerAction::Teleport { location, .. } => location,
+            action => panic!("expected a teleport, found {:?}", action),
+        };
+        assert_eq!(
+            teleport(TriggerTable::new_outdoor(&rom, OutdoorMap::Overworld).unwrap()),
+            7
+        );
+        assert_eq!(
+            teleport(TriggerTable::new_location(&rom, 0x107).unwrap()),
+            0x107
+        );
+
+        assert!(TriggerTable::new_location(&rom, 7)
+            .unwrap()
+            .triggers
+            .is_empty());
+    }
+}